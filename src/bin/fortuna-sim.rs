@@ -0,0 +1,287 @@
+//! Plays many lives with a bot to measure how each character attribute shifts the odds
+//!
+//! ```text
+//! cargo run --release --bin fortuna-sim -- --runs 2000 --format csv --output sim.csv
+//! ```
+
+use bevy::prelude::Vec2;
+use doup_fortuna::{
+    headless::{load_project, load_tuning, Harness, PlayerInput},
+    history::{History, RunRecord},
+    stats::{Intelligence, MentalHealth, SkinColor, Stats, Strength, Wealth},
+    tuning::Tuning,
+    GameState,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::Serialize;
+use std::{fs, sync::Mutex, thread};
+
+const MAX_TICKS: usize = 60 * 60 * 5; // Lose after 5 minutes without reaching the top
+
+fn main() {
+    let runs = get_arg("--runs")
+        .and_then(|runs| runs.parse::<u64>().ok())
+        .unwrap_or(1000);
+    let seed = get_arg("--seed")
+        .and_then(|seed| seed.parse::<u64>().ok())
+        .unwrap_or(0);
+    let format = get_arg("--format").unwrap_or_else(|| String::from("csv"));
+    let output = get_arg("--output").unwrap_or_else(|| format!("fortuna-sim.{format}"));
+
+    if format != "csv" && format != "json" {
+        panic!("Unknown format {format}, use csv or json");
+    }
+
+    let project = load_project();
+    // Edit `assets/fortuna.tuning.ron` to see how the numbers change the odds
+    let tuning = load_tuning();
+    let threads = thread::available_parallelism()
+        .map(|threads| threads.get() as u64)
+        .unwrap_or(1);
+    let lives = Mutex::new(vec![]);
+
+    thread::scope(|scope| {
+        for thread in 0..threads {
+            let (project, tuning, lives) = (&project, &tuning, &lives);
+
+            scope.spawn(move || {
+                for run in (thread..runs).step_by(threads as usize) {
+                    let life = play_life(project.clone(), tuning, seed + run);
+
+                    eprintln!(
+                        "Life {run}: {}",
+                        if life.record.won { "won" } else { "lost" }
+                    );
+                    lives.lock().unwrap().push(life);
+                }
+            });
+        }
+    });
+
+    let mut lives = lives.into_inner().unwrap();
+    lives.sort_by_key(|life| life.seed);
+
+    let results = get_results(&lives);
+    let content = match format.as_str() {
+        "json" => serde_json::to_string_pretty(&results).unwrap(),
+        _ => get_csv(&results),
+    };
+
+    fs::write(&output, content).unwrap_or_else(|err| panic!("Can't write {output}: {err}"));
+
+    let history = History {
+        runs: lives.into_iter().map(|life| life.record).collect(),
+    };
+
+    for (group, win_rates) in history.get_report() {
+        eprintln!(
+            "{group}: {}",
+            win_rates
+                .iter()
+                .map(|win_rate| win_rate.get_text())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+
+    eprintln!("Results saved to {output}");
+}
+
+fn get_arg(name: &str) -> Option<String> {
+    std::env::args().skip_while(|arg| arg != name).nth(1)
+}
+
+struct Life {
+    seed: u64,
+    record: RunRecord,
+    highest_point: f32,
+}
+
+fn play_life(project: bevy_ecs_ldtk::ldtk::LdtkJson, tuning: &Tuning, seed: u64) -> Life {
+    let stats = Stats::from_seed(seed, tuning);
+    let mut harness = Harness::from_project(project, stats.clone(), vec![]);
+    let mut bot = Bot::new(seed);
+
+    for _ in 0..MAX_TICKS {
+        if *harness.state() != GameState::Game {
+            break;
+        }
+
+        let input = match harness.player_position() {
+            Some(position) => bot.get_input(position),
+            None => PlayerInput::default(),
+        };
+
+        harness.step(input);
+    }
+
+    let run_stats = harness.run_stats();
+
+    Life {
+        seed,
+        record: RunRecord::new(
+            &stats,
+            *harness.state() == GameState::WinMenu,
+            run_stats.time,
+            run_stats.goo_hits,
+        ),
+        highest_point: run_stats.highest_point,
+    }
+}
+
+/// Runs and jumps at random, turning around when stuck against a wall. Not smart,
+/// but it plays every character the same way so their results can be compared.
+struct Bot {
+    rng: StdRng,
+    is_going_right: bool,
+    jump_ticks: u32,
+    stuck_ticks: u32,
+    last_position: Option<Vec2>,
+}
+
+impl Bot {
+    fn new(seed: u64) -> Bot {
+        Bot {
+            rng: StdRng::seed_from_u64(seed),
+            is_going_right: false,
+            jump_ticks: 0,
+            stuck_ticks: 0,
+            last_position: None,
+        }
+    }
+
+    fn get_input(&mut self, position: Vec2) -> PlayerInput {
+        let is_stuck = self
+            .last_position
+            .map(|last_position| (position.x - last_position.x).abs() < 0.1)
+            .unwrap_or_default();
+
+        self.stuck_ticks = if is_stuck { self.stuck_ticks + 1 } else { 0 };
+        self.last_position = Some(position);
+
+        if self.stuck_ticks > 30 || self.rng.gen_bool(1.0 / 180.0) {
+            self.is_going_right = !self.is_going_right;
+            self.stuck_ticks = 0;
+        }
+
+        let jump = self.jump_ticks == 0 && self.rng.gen_bool(0.1);
+
+        if jump {
+            // Short taps and full jumps
+            self.jump_ticks = self.rng.gen_range(4..40);
+        }
+
+        self.jump_ticks = self.jump_ticks.saturating_sub(1);
+
+        PlayerInput {
+            left: !self.is_going_right,
+            right: self.is_going_right,
+            jump,
+            jump_held: self.jump_ticks > 0,
+            down: false,
+        }
+    }
+}
+
+/// Attributes a character is born with
+#[derive(Clone, PartialEq, Serialize)]
+struct Character {
+    wealth: Wealth,
+    color: SkinColor,
+    is_male: bool,
+    mental_health: MentalHealth,
+    strength: Strength,
+    intelligence: Intelligence,
+    has_supportive_family: bool,
+}
+
+impl Character {
+    fn new(record: &RunRecord) -> Character {
+        Character {
+            wealth: record.wealth,
+            color: record.color,
+            is_male: record.is_male,
+            mental_health: record.mental_health,
+            strength: record.strength,
+            intelligence: record.intelligence,
+            has_supportive_family: record.has_supportive_family,
+        }
+    }
+}
+
+/// Results of the lives of one combination of attributes
+#[derive(Serialize)]
+struct CharacterResult {
+    #[serde(flatten)]
+    character: Character,
+    runs: usize,
+    wins: usize,
+    win_rate: f32,
+    avg_highest_point: f32,
+    avg_time: f64,
+}
+
+fn get_results(lives: &[Life]) -> Vec<CharacterResult> {
+    let mut results: Vec<CharacterResult> = vec![];
+
+    for life in lives {
+        let character = Character::new(&life.record);
+        let index = match results
+            .iter()
+            .position(|result| result.character == character)
+        {
+            Some(index) => index,
+            None => {
+                results.push(CharacterResult {
+                    character,
+                    runs: 0,
+                    wins: 0,
+                    win_rate: 0.0,
+                    avg_highest_point: 0.0,
+                    avg_time: 0.0,
+                });
+                results.len() - 1
+            }
+        };
+        let result = &mut results[index];
+
+        // Running averages
+        result.runs += 1;
+        result.wins += life.record.won as usize;
+        result.win_rate = result.wins as f32 / result.runs as f32;
+        result.avg_highest_point +=
+            (life.highest_point - result.avg_highest_point) / result.runs as f32;
+        result.avg_time += (life.record.time - result.avg_time) / result.runs as f64;
+    }
+
+    results
+}
+
+fn get_csv(results: &[CharacterResult]) -> String {
+    let mut lines = vec![String::from(
+        "wealth,color,is_male,mental_health,strength,intelligence,has_supportive_family,\
+         runs,wins,win_rate,avg_highest_point,avg_time",
+    )];
+
+    for result in results {
+        let character = &result.character;
+
+        lines.push(format!(
+            "{:?},{:?},{},{:?},{:?},{:?},{},{},{},{:.3},{:.1},{:.1}",
+            character.wealth,
+            character.color,
+            character.is_male,
+            character.mental_health,
+            character.strength,
+            character.intelligence,
+            character.has_supportive_family,
+            result.runs,
+            result.wins,
+            result.win_rate,
+            result.avg_highest_point,
+            result.avg_time,
+        ));
+    }
+
+    lines.join("\n") + "\n"
+}
@@ -1,6 +1,7 @@
 use crate::game::{LifesText, Player, PlayerPositionsRes, Position};
 use crate::loading::UIAssets;
 use crate::stats::{Intelligence, SkinColor, Stats, StatsRes, Strength, Wealth};
+use crate::tuning::TuningRes;
 use crate::ui::{handle_ui_buttons, NORMAL_BUTTON};
 use crate::utils::clean_state;
 use crate::GameState;
@@ -42,6 +43,9 @@ struct ReBornButton;
 #[derive(Component)]
 struct StatsDescription;
 
+#[derive(Component)]
+struct SeedText;
+
 // SYSTEMS
 fn setup_character_menu(stats: Res<StatsRes>, mut commands: Commands, ui_assets: Res<UIAssets>) {
     let button_margin = Rect::all(Val::Px(15.0));
@@ -123,6 +127,37 @@ fn setup_character_menu(stats: Res<StatsRes>, mut commands: Commands, ui_assets:
                         })
                         .insert(StatsDescription);
 
+                    parent
+                        .spawn_bundle(TextBundle {
+                            style: Style {
+                                max_size: Size::new(Val::Px(600.0), Val::Auto),
+                                margin: Rect {
+                                    top: Val::Px(5.0),
+                                    right: Val::Undefined,
+                                    bottom: Val::Undefined,
+                                    left: Val::Undefined,
+                                },
+                                ..Default::default()
+                            },
+                            // Use `Text` directly
+                            text: Text {
+                                alignment: TextAlignment {
+                                    horizontal: HorizontalAlign::Center,
+                                    vertical: VerticalAlign::Center,
+                                },
+                                sections: vec![TextSection {
+                                    value: get_seed_text(&stats.value),
+                                    style: TextStyle {
+                                        font: ui_assets.font.clone(),
+                                        font_size: 20.0,
+                                        color: Color::GRAY,
+                                    },
+                                }],
+                            },
+                            ..Default::default()
+                        })
+                        .insert(SeedText);
+
                     parent.spawn_bundle(TextBundle {
                         style: Style {
                             max_size: Size::new(Val::Px(600.0), Val::Auto),
@@ -255,6 +290,13 @@ fn setup_character_menu(stats: Res<StatsRes>, mut commands: Commands, ui_assets:
         });
 }
 
+fn get_seed_text(stats: &Stats) -> String {
+    match stats.seed {
+        Some(seed) => format!("Seed: {seed}"),
+        None => String::from(""),
+    }
+}
+
 fn add_badges(parent: &mut ChildBuilder, stats: &Stats, ui_assets: &UIAssets) {
     let mut badges: Vec<Handle<Image>> = vec![];
 
@@ -305,12 +347,22 @@ fn handle_start_button(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn handle_reborn_button(
     ui_assets: Res<UIAssets>,
     mut commands: Commands,
     mut stats: ResMut<StatsRes>,
+    tuning: Res<TuningRes>,
     mut stats_desc_query: Query<&mut Text, (With<StatsDescription>, Without<LifesText>)>,
-    mut player_query: Query<&mut Position, With<Player>>,
+    mut seed_text_query: Query<
+        &mut Text,
+        (
+            With<SeedText>,
+            Without<StatsDescription>,
+            Without<LifesText>,
+        ),
+    >,
+    mut player_query: Query<(&mut Position, &mut Player)>,
     mut lifes_query: Query<&mut Text, (With<LifesText>, Without<StatsDescription>)>,
     player_positions: Res<PlayerPositionsRes>,
     interaction_query: Query<&Interaction, (Changed<Interaction>, With<ReBornButton>)>,
@@ -318,11 +370,14 @@ fn handle_reborn_button(
 ) {
     for interaction in interaction_query.iter() {
         if *interaction == Interaction::Clicked {
-            stats.value = Stats::new();
+            *stats = StatsRes::new(Stats::new(&tuning.value));
 
             let mut stats_desc = stats_desc_query.single_mut();
             stats_desc.sections[0].value = stats.value.get_description();
 
+            let mut seed_text = seed_text_query.single_mut();
+            seed_text.sections[0].value = get_seed_text(&stats.value);
+
             // Re-create badges
             let badges_node = badges_query.single();
             commands.entity(badges_node).despawn_descendants();
@@ -338,11 +393,13 @@ fn handle_reborn_button(
             };
 
             let &position_transform = player_positions.value.get(pos).unwrap();
-            let mut player_position = player_query.single_mut();
+            let (mut player_position, mut player) = player_query.single_mut();
 
-            player_position.value = position_transform.translation.truncate();
+            player_position.teleport(position_transform.translation.truncate());
 
             // Update lifes
+            player.lifes = stats.value.lifes;
+
             let mut lifes_text = lifes_query.single_mut();
             lifes_text.sections[1].value = stats.value.lifes.to_string();
         }
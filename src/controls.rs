@@ -0,0 +1,317 @@
+use bevy::{
+    input::{
+        gamepad::{GamepadAxisType, GamepadButtonType},
+        InputSystem,
+    },
+    prelude::*,
+    utils::HashSet,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs};
+
+const CONTROLS_PATH: &str = "controls.ron";
+const DEFAULT_DEADZONE: f32 = 0.3;
+
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ControlsRes {
+            value: Controls::load(),
+        })
+        .init_resource::<ActionsRes>()
+        .add_system_to_stage(CoreStage::PreUpdate, update_actions.after(InputSystem));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    MoveDown,
+    Jump,
+    Restart,
+    Pause,
+}
+
+impl Action {
+    pub const ALL: [Action; 6] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::MoveDown,
+        Action::Jump,
+        Action::Restart,
+        Action::Pause,
+    ];
+
+    pub fn name(&self) -> &str {
+        match self {
+            Action::MoveLeft => "Move left",
+            Action::MoveRight => "Move right",
+            Action::MoveDown => "Move down",
+            Action::Jump => "Jump",
+            Action::Restart => "Restart",
+            Action::Pause => "Pause",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum AxisDirection {
+    Negative,
+    Positive,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    /// Button of any connected gamepad
+    Button(GamepadButtonType),
+    /// Stick of any connected gamepad pushed past the deadzone
+    Axis(GamepadAxisType, AxisDirection),
+}
+
+impl Binding {
+    pub fn name(&self) -> String {
+        match self {
+            Binding::Key(key) => format!("{key:?}"),
+            Binding::Button(button) => format!("Pad {button:?}"),
+            Binding::Axis(axis, AxisDirection::Negative) => format!("Pad {axis:?}-"),
+            Binding::Axis(axis, AxisDirection::Positive) => format!("Pad {axis:?}+"),
+        }
+    }
+}
+
+/// Keyboard keys and gamepad buttons/sticks of each action, saved to `controls.ron`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Controls {
+    pub deadzone: f32,
+    pub bindings: BTreeMap<Action, Vec<Binding>>,
+}
+
+impl Default for Controls {
+    fn default() -> Self {
+        let bindings = [
+            (
+                Action::MoveLeft,
+                vec![
+                    Binding::Key(KeyCode::Left),
+                    Binding::Key(KeyCode::A),
+                    Binding::Button(GamepadButtonType::DPadLeft),
+                    Binding::Axis(GamepadAxisType::LeftStickX, AxisDirection::Negative),
+                ],
+            ),
+            (
+                Action::MoveRight,
+                vec![
+                    Binding::Key(KeyCode::Right),
+                    Binding::Key(KeyCode::D),
+                    Binding::Button(GamepadButtonType::DPadRight),
+                    Binding::Axis(GamepadAxisType::LeftStickX, AxisDirection::Positive),
+                ],
+            ),
+            (
+                Action::MoveDown,
+                vec![
+                    Binding::Key(KeyCode::Down),
+                    Binding::Key(KeyCode::S),
+                    Binding::Button(GamepadButtonType::DPadDown),
+                    Binding::Axis(GamepadAxisType::LeftStickY, AxisDirection::Negative),
+                ],
+            ),
+            (
+                Action::Jump,
+                vec![
+                    Binding::Key(KeyCode::Space),
+                    Binding::Button(GamepadButtonType::South),
+                ],
+            ),
+            (
+                Action::Restart,
+                vec![
+                    Binding::Key(KeyCode::R),
+                    Binding::Button(GamepadButtonType::Select),
+                ],
+            ),
+            (
+                Action::Pause,
+                vec![
+                    Binding::Key(KeyCode::Escape),
+                    Binding::Button(GamepadButtonType::Start),
+                ],
+            ),
+        ];
+
+        Controls {
+            deadzone: DEFAULT_DEADZONE,
+            bindings: bindings.into_iter().collect(),
+        }
+    }
+}
+
+impl Controls {
+    /// Saved controls, or the default ones if there are none (or they are invalid)
+    pub fn load() -> Controls {
+        let content = match fs::read_to_string(CONTROLS_PATH) {
+            Ok(content) => content,
+            Err(_) => return Controls::default(),
+        };
+
+        let mut controls: Controls = ron::from_str(&content).unwrap_or_else(|err| {
+            println!("Can't load {CONTROLS_PATH}: {err}");
+            Controls::default()
+        });
+
+        // Actions added after the file was saved get their default bindings
+        for (action, bindings) in Controls::default().bindings {
+            controls.bindings.entry(action).or_insert(bindings);
+        }
+
+        controls
+    }
+
+    pub fn save(&self) {
+        let result = ron::ser::to_string_pretty(self, Default::default())
+            .map_err(|err| err.to_string())
+            .and_then(|content| fs::write(CONTROLS_PATH, content).map_err(|err| err.to_string()));
+
+        if let Err(err) = result {
+            println!("Can't save {CONTROLS_PATH}: {err}");
+        }
+    }
+
+    pub fn get_bindings(&self, action: Action) -> &[Binding] {
+        self.bindings
+            .get(&action)
+            .map(|bindings| bindings.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Replaces the binding at `index` of the action, past the last one it's added
+    pub fn rebind(&mut self, action: Action, index: usize, binding: Binding) {
+        let bindings = self.bindings.entry(action).or_default();
+
+        match bindings.get_mut(index) {
+            Some(other) => *other = binding,
+            None => bindings.push(binding),
+        }
+    }
+
+    fn is_pressed(
+        &self,
+        action: Action,
+        keys: &Input<KeyCode>,
+        buttons: &Input<GamepadButton>,
+        axes: &Axis<GamepadAxis>,
+        gamepads: &Gamepads,
+    ) -> bool {
+        self.get_bindings(action)
+            .iter()
+            .any(|binding| match *binding {
+                Binding::Key(key) => keys.pressed(key),
+                Binding::Button(button_type) => gamepads
+                    .iter()
+                    .any(|&gamepad| buttons.pressed(GamepadButton(gamepad, button_type))),
+                Binding::Axis(axis_type, direction) => gamepads.iter().any(|&gamepad| {
+                    let value = axes
+                        .get(GamepadAxis(gamepad, axis_type))
+                        .unwrap_or_default();
+
+                    match direction {
+                        AxisDirection::Negative => value < -self.deadzone,
+                        AxisDirection::Positive => value > self.deadzone,
+                    }
+                }),
+            })
+    }
+}
+
+pub struct ControlsRes {
+    pub value: Controls,
+}
+
+/// Actions held down this frame, read this instead of the devices
+#[derive(Default)]
+pub struct ActionsRes {
+    pressed: HashSet<Action>,
+    just_pressed: HashSet<Action>,
+}
+
+impl ActionsRes {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+}
+
+pub fn update_actions(
+    controls: Res<ControlsRes>,
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    gamepads: Res<Gamepads>,
+    mut actions: ResMut<ActionsRes>,
+) {
+    let pressed = Action::ALL
+        .into_iter()
+        .filter(|&action| {
+            controls
+                .value
+                .is_pressed(action, &keys, &buttons, &axes, &gamepads)
+        })
+        .collect::<HashSet<_>>();
+
+    actions.just_pressed = pressed.difference(&actions.pressed).copied().collect();
+    actions.pressed = pressed;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rebind() {
+        let mut controls = Controls::default();
+
+        controls.rebind(Action::MoveLeft, 1, Binding::Key(KeyCode::J));
+
+        // Only the selected binding changes, the other key still works
+        assert_eq!(
+            controls.get_bindings(Action::MoveLeft),
+            [
+                Binding::Key(KeyCode::Left),
+                Binding::Key(KeyCode::J),
+                Binding::Button(GamepadButtonType::DPadLeft),
+                Binding::Axis(GamepadAxisType::LeftStickX, AxisDirection::Negative),
+            ]
+        );
+
+        controls.rebind(
+            Action::Jump,
+            2,
+            Binding::Axis(GamepadAxisType::LeftStickY, AxisDirection::Positive),
+        );
+
+        assert_eq!(
+            controls.get_bindings(Action::Jump),
+            [
+                Binding::Key(KeyCode::Space),
+                Binding::Button(GamepadButtonType::South),
+                Binding::Axis(GamepadAxisType::LeftStickY, AxisDirection::Positive)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_serialize_deserialize() {
+        let mut controls = Controls::default();
+        controls.rebind(Action::MoveLeft, 0, Binding::Key(KeyCode::J));
+
+        let content = ron::ser::to_string_pretty(&controls, Default::default()).unwrap();
+
+        assert_eq!(ron::from_str::<Controls>(&content).unwrap(), controls);
+    }
+}
@@ -0,0 +1,340 @@
+use crate::controls::{Action, AxisDirection, Binding, Controls, ControlsRes};
+use crate::loading::UIAssets;
+use crate::ui::{handle_ui_buttons, NORMAL_BUTTON};
+use crate::utils::clean_state;
+use crate::GameState;
+use bevy::input::gamepad::GamepadAxisType;
+use bevy::prelude::*;
+
+/// Sticks only rebind when pushed this far, not on drift past the deadzone
+const CAPTURE_AXIS_THRESHOLD: f32 = 0.5;
+const AXES: [GamepadAxisType; 4] = [
+    GamepadAxisType::LeftStickX,
+    GamepadAxisType::LeftStickY,
+    GamepadAxisType::RightStickX,
+    GamepadAxisType::RightStickY,
+];
+
+pub struct ControlsMenuPlugin;
+
+impl Plugin for ControlsMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RebindingRes>()
+            .add_system_set(
+                SystemSet::on_enter(GameState::ControlsMenu).with_system(setup_controls_menu),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::ControlsMenu)
+                    .with_system(handle_ui_buttons)
+                    .with_system(handle_binding_buttons)
+                    .with_system(handle_reset_button)
+                    .with_system(handle_back_button)
+                    .with_system(capture_binding)
+                    .with_system(
+                        update_bindings_rows
+                            .after(handle_binding_buttons)
+                            .after(handle_reset_button)
+                            .after(capture_binding),
+                    ),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::ControlsMenu)
+                    .with_system(clean_state::<ControlsMenuStateEntity>),
+            );
+    }
+}
+
+// RESOURCES
+/// Binding of an action waiting for a key/button press to replace it,
+/// the index past the last binding adds one
+#[derive(Default)]
+struct RebindingRes {
+    selected: Option<(Action, usize)>,
+}
+
+// COMPONENTS
+#[derive(Component)]
+struct ControlsMenuStateEntity;
+
+/// Holds the `BindingButton`s of the action
+#[derive(Component)]
+struct BindingsRow(Action);
+
+#[derive(Component)]
+struct BindingButton(Action, usize);
+
+#[derive(Component)]
+struct ResetButton;
+
+#[derive(Component)]
+struct BackButton;
+
+// SYSTEMS
+fn spawn_button(
+    parent: &mut ChildBuilder,
+    ui_assets: &UIAssets,
+    label: &str,
+    component: impl Component,
+) {
+    parent
+        .spawn_bundle(ButtonBundle {
+            style: Style {
+                // Wider for long binding names
+                size: Size::new(Val::Auto, Val::Px(40.0)),
+                min_size: Size::new(Val::Px(120.0), Val::Auto),
+                padding: Rect {
+                    left: Val::Px(10.0),
+                    right: Val::Px(10.0),
+                    ..Default::default()
+                },
+                margin: Rect::all(Val::Px(5.0)),
+                justify_content: JustifyContent::Center, // horizontally center child text
+                align_items: AlignItems::Center,         // vertically center child text
+                ..Default::default()
+            },
+            color: NORMAL_BUTTON.into(),
+            ..Default::default()
+        })
+        .insert(component)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                text: Text::with_section(
+                    label,
+                    TextStyle {
+                        font: ui_assets.font.clone(),
+                        font_size: 24.0,
+                        color: Color::rgb(0.9, 0.9, 0.9),
+                    },
+                    Default::default(),
+                ),
+                ..Default::default()
+            });
+        });
+}
+
+fn setup_controls_menu(
+    mut commands: Commands,
+    ui_assets: Res<UIAssets>,
+    mut rebinding: ResMut<RebindingRes>,
+) {
+    rebinding.selected = None;
+
+    commands
+        .spawn_bundle(UiCameraBundle::default())
+        .insert(ControlsMenuStateEntity);
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                margin: Rect::all(Val::Px(0.0)),
+                flex_direction: FlexDirection::ColumnReverse,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..Default::default()
+            },
+            color: Color::WHITE.into(),
+            ..Default::default()
+        })
+        .insert(ControlsMenuStateEntity)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                style: Style {
+                    margin: Rect {
+                        bottom: Val::Px(20.0),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                text: Text::with_section(
+                    "Controls",
+                    TextStyle {
+                        font: ui_assets.font.clone(),
+                        font_size: 50.0,
+                        color: Color::BLACK,
+                    },
+                    Default::default(),
+                ),
+                ..Default::default()
+            });
+
+            for action in Action::ALL {
+                parent
+                    .spawn_bundle(NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Row,
+                            align_items: AlignItems::Center,
+                            ..Default::default()
+                        },
+                        color: Color::NONE.into(),
+                        ..Default::default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn_bundle(TextBundle {
+                            style: Style {
+                                size: Size::new(Val::Px(150.0), Val::Auto),
+                                ..Default::default()
+                            },
+                            text: Text::with_section(
+                                action.name(),
+                                TextStyle {
+                                    font: ui_assets.font.clone(),
+                                    font_size: 30.0,
+                                    color: Color::BLACK,
+                                },
+                                Default::default(),
+                            ),
+                            ..Default::default()
+                        });
+
+                        parent
+                            .spawn_bundle(NodeBundle {
+                                style: Style {
+                                    flex_direction: FlexDirection::Row,
+                                    align_items: AlignItems::Center,
+                                    ..Default::default()
+                                },
+                                color: Color::NONE.into(),
+                                ..Default::default()
+                            })
+                            .insert(BindingsRow(action));
+                    });
+            }
+
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        margin: Rect {
+                            top: Val::Px(20.0),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                    color: Color::NONE.into(),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    spawn_button(parent, &ui_assets, "Reset", ResetButton);
+                    spawn_button(parent, &ui_assets, "Back", BackButton);
+                });
+        });
+}
+
+fn handle_binding_buttons(
+    mut rebinding: ResMut<RebindingRes>,
+    interaction_query: Query<(&Interaction, &BindingButton), Changed<Interaction>>,
+) {
+    for (interaction, button) in interaction_query.iter() {
+        if *interaction == Interaction::Clicked {
+            rebinding.selected = Some((button.0, button.1));
+        }
+    }
+}
+
+fn handle_reset_button(
+    mut controls: ResMut<ControlsRes>,
+    mut rebinding: ResMut<RebindingRes>,
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<ResetButton>)>,
+) {
+    for interaction in interaction_query.iter() {
+        if *interaction == Interaction::Clicked {
+            rebinding.selected = None;
+            controls.value = Controls::default();
+            controls.value.save();
+        }
+    }
+}
+
+fn handle_back_button(
+    mut app_state: ResMut<State<GameState>>,
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<BackButton>)>,
+) {
+    for interaction in interaction_query.iter() {
+        if *interaction == Interaction::Clicked {
+            app_state.set(GameState::MainMenu).unwrap();
+        }
+    }
+}
+
+/// First key, gamepad button or stick push after clicking a binding
+fn capture_binding(
+    mut controls: ResMut<ControlsRes>,
+    mut rebinding: ResMut<RebindingRes>,
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    gamepads: Res<Gamepads>,
+) {
+    let (action, index) = match rebinding.selected {
+        Some(selected) => selected,
+        None => return,
+    };
+
+    let key = keys.get_just_pressed().next().map(|&key| Binding::Key(key));
+    let button = || {
+        buttons
+            .get_just_pressed()
+            .next()
+            .map(|&GamepadButton(_, button_type)| Binding::Button(button_type))
+    };
+    let axis = || {
+        gamepads.iter().find_map(|&gamepad| {
+            AXES.into_iter().find_map(|axis_type| {
+                let value = axes
+                    .get(GamepadAxis(gamepad, axis_type))
+                    .unwrap_or_default();
+
+                if value < -CAPTURE_AXIS_THRESHOLD {
+                    Some(Binding::Axis(axis_type, AxisDirection::Negative))
+                } else if value > CAPTURE_AXIS_THRESHOLD {
+                    Some(Binding::Axis(axis_type, AxisDirection::Positive))
+                } else {
+                    None
+                }
+            })
+        })
+    };
+
+    if let Some(binding) = key.or_else(button).or_else(axis) {
+        rebinding.selected = None;
+        controls.value.rebind(action, index, binding);
+        controls.value.save();
+    }
+}
+
+/// Respawns the binding buttons, they change in number when one is added or reset
+fn update_bindings_rows(
+    mut commands: Commands,
+    ui_assets: Res<UIAssets>,
+    controls: Res<ControlsRes>,
+    rebinding: Res<RebindingRes>,
+    rows: Query<(Entity, &BindingsRow, Option<&Children>)>,
+    added_rows: Query<(), Added<BindingsRow>>,
+) {
+    if !controls.is_changed() && !rebinding.is_changed() && added_rows.is_empty() {
+        return;
+    }
+
+    for (entity, row, children) in rows.iter() {
+        let action = row.0;
+
+        for &child in children.iter().flat_map(|children| children.iter()) {
+            commands.entity(child).despawn_recursive();
+        }
+
+        commands.entity(entity).with_children(|parent| {
+            let bindings = controls.value.get_bindings(action);
+
+            for index in 0..=bindings.len() {
+                let label = match bindings.get(index) {
+                    _ if rebinding.selected == Some((action, index)) => String::from("Press..."),
+                    Some(binding) => binding.name(),
+                    None => String::from("+"),
+                };
+
+                spawn_button(parent, &ui_assets, &label, BindingButton(action, index));
+            }
+        });
+    }
+}
@@ -1,66 +1,49 @@
 use bevy::{prelude::*, sprite::collide_aabb::collide};
 use bevy_ecs_ldtk::prelude::*;
 
-use crate::stats::{SkinColor, StatsRes, Wealth};
+use crate::{stats::StatsRes, tuning::TuningRes};
 
-use super::{Player, Position, PLAYER_HEIGHT, PLAYER_WIDTH};
-
-pub const BOUNCER_FORCE: f32 = 2500.0;
-pub const BOUNCER_DURATION: f32 = 0.5;
-
-#[derive(Debug)]
-enum BouncerType {
-    WealthRich,
-    SkinColorLight,
-}
+use super::{
+    events::BounceEvent,
+    physics::PhysicsTime,
+    rule::Rule,
+    validation::{get_bool, get_field, join_errors, wrong_type},
+    Player, Position, PLAYER_HEIGHT, PLAYER_WIDTH,
+};
 
 #[derive(Debug, Component)]
 pub struct Bouncer {
-    allow: BouncerType,
+    /// Who can go through, from the LDtk `type` field
+    allow: Rule,
     direction: f32,
 }
 
-pub fn get_bouncer_from_entity_instance(entity: &EntityInstance) -> Bouncer {
-    let bouncer_type_cfg = entity
-        .field_instances
-        .iter()
-        .filter(|field| field.identifier == "type")
-        .collect::<Vec<_>>();
-
-    let push_left_cfg = entity
-        .field_instances
-        .iter()
-        .filter(|field| field.identifier == "push_left")
-        .collect::<Vec<_>>();
-
-    let allow = if let FieldValue::String(Some(bouncer_type)) = bouncer_type_cfg[0].value.clone() {
-        match bouncer_type.as_str() {
-            "rich" => BouncerType::WealthRich,
-            "skin_light" => BouncerType::SkinColorLight,
-            _ => BouncerType::WealthRich,
-        }
-    } else {
-        BouncerType::WealthRich
-    };
-
-    let direction: f32 = if let FieldValue::Bool(push_left) = push_left_cfg[0].value {
-        if push_left {
-            -1.0
-        } else {
-            1.0
-        }
-    } else {
-        -1.0
-    };
-
-    Bouncer { allow, direction }
+pub fn get_bouncer_from_entity_instance(entity: &EntityInstance) -> Result<Bouncer, String> {
+    let allow = get_field(entity, "type").and_then(|field| match &field.value {
+        FieldValue::String(Some(bouncer_type)) => Rule::parse(bouncer_type)
+            .map_err(|err| format!("invalid `type` rule `{bouncer_type}`: {err}")),
+        FieldValue::String(None) => Err(String::from("empty `type` rule")),
+        _ => Err(wrong_type(field, "String")),
+    });
+
+    let direction = get_bool(entity, "push_left").map(|push_left| match push_left {
+        true => -1.0,
+        false => 1.0,
+    });
+
+    match (allow, direction) {
+        (Ok(allow), Ok(direction)) => Ok(Bouncer { allow, direction }),
+        (allow, direction) => Err(join_errors([allow.err(), direction.err()])),
+    }
 }
 
 pub fn bounce_player(
     stats: Res<StatsRes>,
-    time: Res<Time>,
+    tuning: Res<TuningRes>,
+    physics_time: Res<PhysicsTime>,
     mut player_query: Query<(&mut Position, &mut Player), (With<Player>, Without<Bouncer>)>,
     bouncer_query: Query<(&Transform, &Sprite, &Bouncer), (With<Bouncer>, Without<Player>)>,
+    mut bounce_event: EventWriter<BounceEvent>,
 ) {
     let (mut player_position, mut player) = player_query.single_mut();
 
@@ -72,12 +55,7 @@ pub fn bounce_player(
             bouncer_sprite.custom_size.unwrap(),
         );
 
-        let allow = match bouncer.allow {
-            BouncerType::SkinColorLight => stats.value.color == SkinColor::Light,
-            BouncerType::WealthRich => stats.value.wealth == Wealth::Rich,
-        };
-
-        if !allow && collision.is_some() {
+        if collision.is_some() && !bouncer.allow.eval(&stats.value) {
             if bouncer.direction == 1.0 {
                 player_position.value.x += 2.0 * PLAYER_WIDTH;
             } else {
@@ -87,8 +65,13 @@ pub fn bounce_player(
             if player.bounce_force.is_none() {
                 println!("You're not allowed here: {:?}", bouncer);
 
-                player.bounce_force = Some(BOUNCER_FORCE * bouncer.direction);
-                player.blink(time.seconds_since_startup(), Some(BOUNCER_DURATION as f64));
+                player.bounce_force = Some(tuning.value.bouncer_force * bouncer.direction);
+                player.blink(
+                    physics_time.elapsed(),
+                    Some(tuning.value.bouncer_duration as f64),
+                );
+
+                bounce_event.send(BounceEvent);
             }
         }
     }
@@ -0,0 +1,64 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::{LdtkAsset, LevelEvent, LevelSelection};
+
+use crate::loading::GameAssets;
+
+/// Levels of the LDtk file, played in order
+pub struct CampaignRes {
+    pub level: usize,
+    pub level_count: usize,
+    /// The selected level hasn't spawned yet
+    pub is_loading: bool,
+}
+
+impl Default for CampaignRes {
+    fn default() -> Self {
+        CampaignRes {
+            level: 0,
+            level_count: 1,
+            is_loading: true,
+        }
+    }
+}
+
+impl CampaignRes {
+    pub fn is_last_level(&self) -> bool {
+        self.level + 1 >= self.level_count
+    }
+
+    pub fn next_level(&mut self, level_selection: &mut LevelSelection) {
+        self.level += 1;
+        self.is_loading = true;
+        *level_selection = LevelSelection::Index(self.level);
+    }
+}
+
+pub fn setup_campaign(
+    game_assets: Res<GameAssets>,
+    ldtk_assets: Option<Res<Assets<LdtkAsset>>>,
+    mut campaign: ResMut<CampaignRes>,
+    mut level_selection: ResMut<LevelSelection>,
+) {
+    // There are no LDtk assets when running headless, the harness sets the level count
+    let level_count = ldtk_assets
+        .and_then(|ldtk_assets| {
+            ldtk_assets
+                .get(&game_assets.map)
+                .map(|ldtk| ldtk.project.levels.len())
+        })
+        .unwrap_or(campaign.level_count);
+
+    *campaign = CampaignRes {
+        level_count,
+        ..Default::default()
+    };
+    *level_selection = LevelSelection::Index(0);
+}
+
+pub fn level_loaded(mut campaign: ResMut<CampaignRes>, mut level_events: EventReader<LevelEvent>) {
+    for event in level_events.iter() {
+        if let LevelEvent::Transformed(_) = event {
+            campaign.is_loading = false;
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use bevy::{prelude::*, sprite::collide_aabb::collide};
+
+use crate::stats::StatsRes;
+
+use super::{player::PLAYER_HEIGHT_HALF, Player, Position, PLAYER_HEIGHT, PLAYER_WIDTH};
+
+pub const CHECKPOINT_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.4);
+const REACHED_CHECKPOINT_COLOR: Color = Color::rgb(0.3, 0.8, 0.4);
+
+/// Where the player respawns after losing a life, once touched.
+/// Placed standing on the floor, the player respawns at its bottom.
+#[derive(Component)]
+pub struct Checkpoint {
+    respawn: Vec2,
+}
+
+impl Checkpoint {
+    /// `center` and `size` of the LDtk entity
+    pub fn new(center: Vec2, size: Vec2) -> Checkpoint {
+        Checkpoint {
+            respawn: Vec2::new(center.x, center.y - size.y / 2.0 + PLAYER_HEIGHT_HALF),
+        }
+    }
+}
+
+pub fn checkpoint_collision(
+    stats: Res<StatsRes>,
+    mut player_query: Query<(&Position, &mut Player)>,
+    mut checkpoints: Query<(&Checkpoint, &Transform, &mut Sprite)>,
+) {
+    if !stats.value.can_use_checkpoints {
+        return;
+    }
+
+    let (player_position, mut player) = player_query.single_mut();
+
+    for (checkpoint, transform, mut sprite) in checkpoints.iter_mut() {
+        let is_touching = collide(
+            player_position.value.extend(1.0),
+            Vec2::new(PLAYER_WIDTH, PLAYER_HEIGHT),
+            transform.translation,
+            sprite.custom_size.unwrap(),
+        )
+        .is_some();
+
+        if is_touching && player.checkpoint != Some(checkpoint.respawn) {
+            player.checkpoint = Some(checkpoint.respawn);
+        }
+
+        sprite.color = if player.checkpoint == Some(checkpoint.respawn) {
+            REACHED_CHECKPOINT_COLOR
+        } else {
+            CHECKPOINT_COLOR
+        };
+    }
+}
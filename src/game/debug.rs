@@ -0,0 +1,209 @@
+use bevy::prelude::*;
+
+use crate::GameState;
+
+use super::{
+    bouncer::Bouncer,
+    camera::GameCamera,
+    goal::Goal,
+    obstacles::{get_tile_bbox, BBox, Obstacle, Point},
+    GameStateEntity, ObstaclesRes, Player, Position, PLAYER_HEIGHT, PLAYER_WIDTH, TILE_SIZE,
+};
+
+const DEBUG_KEY: KeyCode = KeyCode::F1;
+/// Tiles drawn around the camera, the view is 640x360px
+const DEBUG_RANGE_X: i32 = 22;
+const DEBUG_RANGE_Y: i32 = 13;
+const DEBUG_Z: f32 = 600.0;
+const WALL_COLOR: Color = Color::rgba(1.0, 0.0, 0.0, 0.25);
+const ONE_WAY_COLOR: Color = Color::rgba(0.0, 0.4, 1.0, 0.25);
+const HIT_COLOR: Color = Color::rgba(1.0, 0.8, 0.0, 0.6);
+const SWEEP_COLOR: Color = Color::FUCHSIA;
+const PLAYER_COLOR: Color = Color::LIME_GREEN;
+const GOAL_COLOR: Color = Color::rgba(0.0, 1.0, 0.0, 0.3);
+const BOUNCER_COLOR: Color = Color::rgba(1.0, 0.5, 0.0, 0.3);
+
+/// Collision shapes overlay, toggled with F1
+pub struct DebugPlugin;
+
+impl Plugin for DebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::Game)
+                .with_system(toggle_collision_debug)
+                .with_system(draw_collision_debug.after(toggle_collision_debug))
+                .with_system(show_triggers.after(toggle_collision_debug)),
+        );
+    }
+}
+
+/// What the last physics step checked, filled by `player_movement` while enabled
+#[derive(Default)]
+pub struct CollisionDebugRes {
+    pub enabled: bool,
+    /// Areas swept by the player this step, horizontal and vertical
+    pub sweeps: Vec<BBox<f32>>,
+    /// Tiles from `get_obstacle_list` in the swept areas
+    pub hits: Vec<Point<i32>>,
+}
+
+impl CollisionDebugRes {
+    pub fn clear(&mut self) {
+        self.sweeps.clear();
+        self.hits.clear();
+    }
+
+    pub fn add_sweep(&mut self, bbox: &BBox<f32>, obstacles: &[&Obstacle]) {
+        if self.enabled {
+            self.sweeps.push(bbox.clone());
+            self.hits
+                .extend(obstacles.iter().map(|obstacle| obstacle.pos.clone()));
+        }
+    }
+}
+
+#[derive(Component)]
+struct DebugShape;
+
+fn toggle_collision_debug(
+    keyboard: Res<Input<KeyCode>>,
+    mut collision_debug: ResMut<CollisionDebugRes>,
+) {
+    if keyboard.just_pressed(DEBUG_KEY) {
+        collision_debug.enabled = !collision_debug.enabled;
+    }
+}
+
+fn add_rect(commands: &mut Commands, bbox: &BBox<f32>, color: Color) {
+    let size = Vec2::new(bbox.right - bbox.left, bbox.top - bbox.bottom);
+
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color,
+                custom_size: Some(size),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(
+                bbox.left + size.x / 2.0,
+                bbox.bottom + size.y / 2.0,
+                DEBUG_Z,
+            ),
+            ..Default::default()
+        })
+        .insert(DebugShape)
+        .insert(GameStateEntity);
+}
+
+/// Rect borders, 1px wide
+fn add_outline(commands: &mut Commands, bbox: &BBox<f32>, color: Color) {
+    let BBox {
+        left,
+        bottom,
+        right,
+        top,
+    } = bbox.clone();
+
+    add_rect(
+        commands,
+        &BBox::new(left, bottom, right, bottom + 1.0),
+        color,
+    );
+    add_rect(commands, &BBox::new(left, top - 1.0, right, top), color);
+    add_rect(commands, &BBox::new(left, bottom, left + 1.0, top), color);
+    add_rect(commands, &BBox::new(right - 1.0, bottom, right, top), color);
+}
+
+/// Shapes are re-created every frame, they only live while enabled
+fn draw_collision_debug(
+    mut commands: Commands,
+    collision_debug: Res<CollisionDebugRes>,
+    obstacles: Res<ObstaclesRes>,
+    shapes_query: Query<Entity, With<DebugShape>>,
+    cameras: Query<&Transform, With<GameCamera>>,
+    player_query: Query<&Position, With<Player>>,
+) {
+    for entity in shapes_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    if !collision_debug.enabled {
+        return;
+    }
+
+    if let Ok(camera) = cameras.get_single() {
+        let center_x = (camera.translation.x / TILE_SIZE).floor() as i32;
+        let center_y = (camera.translation.y / TILE_SIZE).floor() as i32;
+
+        for y in (center_y - DEBUG_RANGE_Y)..=(center_y + DEBUG_RANGE_Y) {
+            for x in (center_x - DEBUG_RANGE_X)..=(center_x + DEBUG_RANGE_X) {
+                if let Some(obstacle) = obstacles.map.get(&Point(x, y)) {
+                    let color = if obstacle.is_one_way {
+                        ONE_WAY_COLOR
+                    } else {
+                        WALL_COLOR
+                    };
+
+                    add_rect(&mut commands, &get_tile_bbox(&obstacle.pos), color);
+                }
+            }
+        }
+    }
+
+    // Platform landing surfaces at least 1px thick, solid platforms whole
+    for platform in obstacles.platforms.iter() {
+        let BBox {
+            left,
+            bottom,
+            right,
+            top,
+        } = platform.bbox;
+
+        if platform.is_one_way {
+            add_rect(
+                &mut commands,
+                &BBox::new(left, bottom - 1.0, right, top),
+                ONE_WAY_COLOR,
+            );
+        } else {
+            add_rect(&mut commands, &platform.bbox, WALL_COLOR);
+        }
+    }
+
+    for tile in collision_debug.hits.iter() {
+        add_rect(&mut commands, &get_tile_bbox(tile), HIT_COLOR);
+    }
+
+    for sweep in collision_debug.sweeps.iter() {
+        add_outline(&mut commands, sweep, SWEEP_COLOR);
+    }
+
+    if let Ok(position) = player_query.get_single() {
+        let half_size = Vec2::new(PLAYER_WIDTH, PLAYER_HEIGHT) / 2.0;
+        let bottom_left = position.value - half_size;
+        let top_right = position.value + half_size;
+
+        add_outline(
+            &mut commands,
+            &BBox::new(bottom_left.x, bottom_left.y, top_right.x, top_right.y),
+            PLAYER_COLOR,
+        );
+    }
+}
+
+/// Goal and bouncers are invisible areas, show them with the overlay
+fn show_triggers(
+    collision_debug: Res<CollisionDebugRes>,
+    mut goal_query: Query<(&mut Visibility, &mut Sprite), (With<Goal>, Without<Bouncer>)>,
+    mut bouncer_query: Query<(&mut Visibility, &mut Sprite), (With<Bouncer>, Without<Goal>)>,
+) {
+    for (mut visibility, mut sprite) in goal_query.iter_mut() {
+        visibility.is_visible = collision_debug.enabled;
+        sprite.color = GOAL_COLOR;
+    }
+
+    for (mut visibility, mut sprite) in bouncer_query.iter_mut() {
+        visibility.is_visible = collision_debug.enabled;
+        sprite.color = BOUNCER_COLOR;
+    }
+}
@@ -3,36 +3,43 @@ use rand::Rng;
 
 use crate::stats::StatsRes;
 
-use super::{DepressedText, Player};
+use super::{events::DepressionEvent, physics::PhysicsTime, Player};
 
 // DEPRESSIVE STATE
 pub const MIN_DEPRE_DURATION: f64 = 2.0;
 pub const MAX_DEPRE_DURATION: f64 = 6.0;
 pub const MIN_TIME_BETWEEN_DEPRE: f64 = 10.0;
 
-pub fn show_depressed_text(
-    time: Res<Time>,
-    mut depressed_text_query: Query<
-        (&mut Visibility, &mut Transform),
-        (With<DepressedText>, Without<Player>),
-    >,
-    player_query: Query<(&Player, &Transform), With<Player>>,
+pub fn trigger_depression(
+    mut stats: ResMut<StatsRes>,
+    physics_time: Res<PhysicsTime>,
+    mut players: Query<&mut Player>,
+    mut depression_event: EventWriter<DepressionEvent>,
 ) {
-    let (player, transform) = player_query.single();
-    let (mut depre_visibility, mut depre_transform) = depressed_text_query.single_mut();
-
-    depre_visibility.is_visible = player.depressed_until > time.seconds_since_startup();
-    depre_transform.translation = transform.translation + Vec3::new(0.0, 24.0, 0.0);
-}
-
-pub fn trigger_depression(stats: Res<StatsRes>, time: Res<Time>, mut players: Query<&mut Player>) {
     let mut player = players.single_mut();
 
     let can_get_depressed = stats.value.is_depressive
-        && (player.depressed_until + MIN_TIME_BETWEEN_DEPRE) < time.seconds_since_startup();
-
-    if can_get_depressed && rand::thread_rng().gen_range(0.0..1.0) < stats.value.depre_chance {
-        player.depressed_until = time.seconds_since_startup()
-            + rand::thread_rng().gen_range(MIN_DEPRE_DURATION..MAX_DEPRE_DURATION);
+        && (player.depressed_until + MIN_TIME_BETWEEN_DEPRE) < physics_time.elapsed();
+
+    if can_get_depressed && stats.rng.gen_range(0.0..1.0) < stats.value.depre_chance {
+        depress(
+            &mut stats,
+            physics_time.elapsed(),
+            &mut player,
+            &mut depression_event,
+        );
     }
 }
+
+/// Starts a depression episode of random duration
+pub fn depress(
+    stats: &mut StatsRes,
+    elapsed: f64,
+    player: &mut Player,
+    depression_event: &mut EventWriter<DepressionEvent>,
+) {
+    let duration = stats.rng.gen_range(MIN_DEPRE_DURATION..MAX_DEPRE_DURATION);
+
+    player.depressed_until = elapsed + duration;
+    depression_event.send(DepressionEvent);
+}
@@ -0,0 +1,161 @@
+use bevy::prelude::*;
+use bevy_inspector_egui::{
+    bevy_egui::{EguiContext, EguiPlugin},
+    egui, Context, Inspectable,
+};
+
+use crate::{stats::StatsRes, tuning::TuningRes};
+
+use super::{
+    debug::CollisionDebugRes,
+    depression::depress,
+    events::{DepressionEvent, PsychosisEvent},
+    goo::Goo,
+    obstacles::{to_tile_space, Point},
+    physics::PhysicsTime,
+    psychosis::start_psychosis,
+    setup::is_game_in_stack,
+    LifesText, ObstaclesRes, Player, PlayerPositionsRes, Position, Velocity,
+};
+
+/// Tiles around the player listed in the obstacles section
+const NEARBY_TILES: i32 = 2;
+
+/// Panel to inspect and tweak the game while playing, to try edge cases
+/// without recompiling. Only built with the `dev` feature.
+pub struct DevPlugin;
+
+impl Plugin for DevPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(EguiPlugin)
+            .add_system(dev_panel.with_run_criteria(is_game_in_stack));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn dev_panel(
+    mut egui_context: ResMut<EguiContext>,
+    mut collision_debug: ResMut<CollisionDebugRes>,
+    mut stats: ResMut<StatsRes>,
+    tuning: Res<TuningRes>,
+    mut physics_time: ResMut<PhysicsTime>,
+    obstacles: Res<ObstaclesRes>,
+    player_positions: Res<PlayerPositionsRes>,
+    mut player_query: Query<(&mut Player, &mut Position, &mut Velocity)>,
+    mut goo_query: Query<&mut Goo>,
+    mut lifes_query: Query<&mut Text, With<LifesText>>,
+    mut depression_event: EventWriter<DepressionEvent>,
+    mut psychosis_event: EventWriter<PsychosisEvent>,
+) {
+    let ctx = egui_context.ctx_mut();
+    let mut context = Context::new_shared(Some(ctx));
+
+    egui::Window::new("Dev").show(ctx, |ui| {
+        ui.checkbox(&mut collision_debug.enabled, "Collision overlay (F1)");
+
+        // Slow motion to look at a jump or a collision step by step
+        let mut scale = physics_time.scale();
+
+        if ui
+            .add(egui::Slider::new(&mut scale, 0.1..=2.0).text("Game speed"))
+            .changed()
+        {
+            physics_time.set_scale(scale);
+        }
+
+        egui::CollapsingHeader::new("Stats").show(ui, |ui| {
+            if stats.value.ui(ui, (), &mut context.with_id(0)) {
+                // Keep the movement values in sync with the attributes
+                stats.value.apply_tuning(&tuning.value);
+            }
+        });
+
+        if let Ok((mut player, mut position, mut velocity)) = player_query.get_single_mut() {
+            egui::CollapsingHeader::new("Player").show(ui, |ui| {
+                player.ui(ui, (), &mut context.with_id(1));
+                ui.label("Velocity");
+                velocity.ui(ui, (), &mut context.with_id(2));
+            });
+
+            ui.horizontal(|ui| {
+                if ui.button("Depress").clicked() {
+                    depress(
+                        &mut stats,
+                        physics_time.elapsed(),
+                        &mut player,
+                        &mut depression_event,
+                    );
+                }
+
+                if ui.button("Psychosis").clicked() {
+                    start_psychosis(
+                        &mut stats,
+                        &tuning.value,
+                        physics_time.elapsed(),
+                        &mut player,
+                        &mut psychosis_event,
+                    );
+                }
+
+                if ui.button("Refill lifes").clicked() {
+                    player.lifes = stats.value.lifes;
+
+                    for mut lifes_text in lifes_query.iter_mut() {
+                        lifes_text.sections[1].value = player.lifes.to_string();
+                    }
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("Teleport to start");
+
+                for (index, transform) in player_positions.value.iter().enumerate() {
+                    if ui.button(index.to_string()).clicked() {
+                        position.teleport(transform.translation.truncate());
+                        velocity.x = 0.0;
+                        velocity.y = 0.0;
+                    }
+                }
+            });
+
+            egui::CollapsingHeader::new("Obstacles").show(ui, |ui| {
+                let one_ways = obstacles.map.values().filter(|obs| obs.is_one_way).count();
+                let Point(x, y) = to_tile_space(&position.value);
+
+                ui.label(format!(
+                    "{} tiles, {one_ways} one-way. Player at ({x}, {y})",
+                    obstacles.map.len()
+                ));
+
+                for tile_y in (y - NEARBY_TILES..=y + NEARBY_TILES).rev() {
+                    let row = (x - NEARBY_TILES..=x + NEARBY_TILES)
+                        .map(|tile_x| match obstacles.map.get(&Point(tile_x, tile_y)) {
+                            Some(obstacle) if obstacle.is_one_way => '-',
+                            Some(_) => '#',
+                            None if (tile_x, tile_y) == (x, y) => '@',
+                            None => '.',
+                        })
+                        .collect::<String>();
+
+                    ui.monospace(row);
+                }
+            });
+        }
+
+        if let Ok(mut goo) = goo_query.get_single_mut() {
+            egui::CollapsingHeader::new("Goo").show(ui, |ui| {
+                goo.ui(ui, (), &mut context.with_id(3));
+            });
+
+            let label = if goo.is_frozen {
+                "Unfreeze goo"
+            } else {
+                "Freeze goo"
+            };
+
+            if ui.button(label).clicked() {
+                goo.is_frozen = !goo.is_frozen;
+            }
+        }
+    });
+}
@@ -1,6 +1,6 @@
 use bevy::prelude::*;
 
-use super::PlayerDirection;
+use super::{psychosis::Hallucination, PlayerDirection};
 
 #[derive(Debug)]
 pub struct JumpEvent {
@@ -18,6 +18,36 @@ pub struct CeilHitEvent {
     pub position: Vec2,
 }
 
+/// Started sliding down a wall on the `side` of the player
+pub struct WallSlideEvent {
+    pub position: Vec2,
+    pub side: PlayerDirection,
+}
+
+/// Jumped off a wall, the velocity points away from it
+pub struct WallJumpEvent {
+    pub position: Vec2,
+    pub velocity: Vec2,
+}
+
+/// The goo reached the player, taking a life
+pub struct GooHitEvent;
+
+/// The supportive family caught the player instead of the goo
+pub struct FamilyRescueEvent {
+    pub position: Vec2,
+}
+
+/// A bouncer didn't let the player through
+pub struct BounceEvent;
+
+pub struct DepressionEvent;
+
+pub struct PsychosisEvent {
+    pub duration: f64,
+    pub hallucination: Hallucination,
+}
+
 #[derive(Debug)]
 pub struct DirectionChangeEvent {
     pub position: Vec2,
@@ -1,13 +1,16 @@
 use crate::GameState;
 
-use super::{Player, Position, PLAYER_HEIGHT, PLAYER_WIDTH};
+use super::{campaign::CampaignRes, Player, Position, PLAYER_HEIGHT, PLAYER_WIDTH};
 use bevy::{prelude::*, sprite::collide_aabb::collide};
+use bevy_ecs_ldtk::LevelSelection;
 
 #[derive(Component)]
 pub struct Goal;
 
 pub fn goal_collision(
     mut app_state: ResMut<State<GameState>>,
+    mut campaign: ResMut<CampaignRes>,
+    mut level_selection: ResMut<LevelSelection>,
     mut player_query: Query<&Position, (With<Player>, Without<Goal>)>,
     goal_query: Query<(&Transform, &Sprite), (With<Goal>, Without<Player>)>,
 ) {
@@ -22,6 +25,11 @@ pub fn goal_collision(
     )
     .is_some()
     {
-        app_state.set(GameState::WinMenu).unwrap();
+        if campaign.is_last_level() {
+            // Several physics steps can run before the state changes
+            app_state.overwrite_set(GameState::WinMenu).unwrap();
+        } else {
+            campaign.next_level(&mut level_selection);
+        }
     }
 }
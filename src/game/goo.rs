@@ -1,94 +1,394 @@
 use bevy::prelude::*;
+use bevy_ecs_ldtk::{prelude::*, LevelEvent};
+use std::f32::consts::PI;
 
-use crate::GameState;
+use crate::{
+    stats::StatsRes,
+    tuning::{Tuning, TuningRes},
+    GameState,
+};
 
 use super::{
     camera::GameCamera,
-    obstacles::{get_first_obstacle_pos_downward, to_tile_space},
-    GameStateEntity, ObstaclesRes, Player, Position, TILE_SIZE,
+    events::{FamilyRescueEvent, GooHitEvent},
+    obstacles::{get_first_obstacle_pos_downward, to_tile_space, Point},
+    physics::{PhysicsTime, PHYSICS_TIMESTEP},
+    validation::{get_field, get_float, get_positive_float, join_errors, wrong_type},
+    GameStateEntity, ObstaclesRes, Player, Position, Velocity, TILE_SIZE,
 };
 
+// Levels without a `GooSettings` entity
 const GOO_INITIAL_POS: f32 = -50.0;
-const GOO_SPEED: f32 = 32.0;
 const GOO_SIN_AMPLITUDE: f32 = 8.0;
-const GOO_HIT_REGRESS: f32 = 64.0;
+const GOO_SIN_PERIOD: f32 = PI;
+
+/// How the goo rising speed changes over the level
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum GooCurve {
+    #[default]
+    Constant,
+    /// Px/s gained every second
+    Accelerating(f32),
+    /// Rises for `every` seconds, then stops for `pause` seconds
+    Pausing { every: f64, pause: f64 },
+}
+
+/// Goo pacing of a level, from its `GooSettings` LDtk entity
+#[derive(Clone, Debug, PartialEq)]
+pub struct GooSettings {
+    /// Px from the bottom of the level
+    start: f32,
+    /// Px/s
+    speed: f32,
+    curve: GooCurve,
+    wave_amplitude: f32,
+    /// Seconds
+    wave_period: f32,
+    /// The goo speeds up while the player is higher above it than this, 0 to never
+    catch_up_distance: f32,
+    /// Px/s added once the player is twice `catch_up_distance` above
+    catch_up_speed: f32,
+    color: Color,
+}
+
+impl GooSettings {
+    pub fn from_tuning(tuning: &Tuning) -> GooSettings {
+        GooSettings {
+            start: GOO_INITIAL_POS,
+            speed: tuning.goo_speed,
+            curve: GooCurve::Constant,
+            wave_amplitude: GOO_SIN_AMPLITUDE,
+            wave_period: GOO_SIN_PERIOD,
+            catch_up_distance: tuning.goo_catch_up_distance,
+            catch_up_speed: tuning.goo_catch_up_speed,
+            color: Color::BLACK,
+        }
+    }
+
+    /// Rising speed `time` seconds into the level, with the player `distance` px above
+    fn get_speed(&self, time: f64, distance: f32) -> f32 {
+        let speed = match self.curve {
+            GooCurve::Constant => self.speed,
+            GooCurve::Accelerating(acceleration) => self.speed + acceleration * time as f32,
+            GooCurve::Pausing { every, pause } if time % (every + pause) < every => self.speed,
+            GooCurve::Pausing { .. } => 0.0,
+        };
+
+        if self.catch_up_distance > 0.0 {
+            let catch_up =
+                ((distance - self.catch_up_distance) / self.catch_up_distance).clamp(0.0, 1.0);
+
+            speed + catch_up * self.catch_up_speed
+        } else {
+            speed
+        }
+    }
+
+    fn get_wave(&self, elapsed: f64) -> f32 {
+        (elapsed as f32 * 2.0 * PI / self.wave_period).sin() * self.wave_amplitude
+    }
+}
+
+/// Settings of the current level, `None` uses the tuning ones
+#[derive(Default)]
+pub struct GooSettingsRes {
+    pub value: Option<GooSettings>,
+}
+
+impl GooSettingsRes {
+    pub fn get(&self, tuning: &Tuning) -> GooSettings {
+        self.value
+            .clone()
+            .unwrap_or_else(|| GooSettings::from_tuning(tuning))
+    }
+}
+
+fn get_non_negative_float(entity: &EntityInstance, identifier: &str) -> Result<f32, String> {
+    get_float(entity, identifier).and_then(|value| match value {
+        value if value >= 0.0 => Ok(value),
+        value => Err(format!(
+            "field `{identifier}` can't be negative, got {value}"
+        )),
+    })
+}
+
+pub fn get_goo_settings_from_entity_instance(
+    entity: &EntityInstance,
+) -> Result<GooSettings, String> {
+    let start = get_float(entity, "start");
+    let speed = get_non_negative_float(entity, "speed");
+    let curve = get_field(entity, "curve").and_then(|field| match &field.value {
+        FieldValue::String(Some(curve)) => match curve.as_str() {
+            "constant" => Ok(GooCurve::Constant),
+            "accelerating" => get_float(entity, "acceleration").map(GooCurve::Accelerating),
+            "pausing" => get_positive_float(entity, "pause_every").and_then(|every| {
+                get_positive_float(entity, "pause_for").map(|pause| GooCurve::Pausing {
+                    every: every as f64,
+                    pause: pause as f64,
+                })
+            }),
+            _ => Err(format!(
+                "unknown `curve` `{curve}`, it should be constant, accelerating or pausing"
+            )),
+        },
+        FieldValue::String(None) => Err(String::from("empty `curve`")),
+        _ => Err(wrong_type(field, "String")),
+    });
+    let color = get_field(entity, "color").and_then(|field| match field.value {
+        FieldValue::Color(color) => Ok(color),
+        _ => Err(wrong_type(field, "Color")),
+    });
+
+    let wave_amplitude = get_non_negative_float(entity, "wave_amplitude");
+    let wave_period = get_positive_float(entity, "wave_period");
+    let catch_up_distance = get_non_negative_float(entity, "catch_up_distance");
+    let catch_up_speed = get_non_negative_float(entity, "catch_up_speed");
+
+    match (
+        start,
+        speed,
+        curve,
+        wave_amplitude,
+        wave_period,
+        catch_up_distance,
+        catch_up_speed,
+        color,
+    ) {
+        (
+            Ok(start),
+            Ok(speed),
+            Ok(curve),
+            Ok(wave_amplitude),
+            Ok(wave_period),
+            Ok(catch_up_distance),
+            Ok(catch_up_speed),
+            Ok(color),
+        ) => Ok(GooSettings {
+            start,
+            speed,
+            curve,
+            wave_amplitude,
+            wave_period,
+            catch_up_distance,
+            catch_up_speed,
+            color,
+        }),
+        (
+            start,
+            speed,
+            curve,
+            wave_amplitude,
+            wave_period,
+            catch_up_distance,
+            catch_up_speed,
+            color,
+        ) => Err(join_errors([
+            start.err(),
+            speed.err(),
+            curve.err(),
+            wave_amplitude.err(),
+            wave_period.err(),
+            catch_up_distance.err(),
+            catch_up_speed.err(),
+            color.err(),
+        ])),
+    }
+}
 
 #[derive(Component)]
+#[cfg_attr(feature = "dev", derive(bevy_inspector_egui::Inspectable))]
 pub struct Goo {
     y: f32,
     start_time: f64,
+    /// Px risen since the level started
+    risen: f32,
     regress: f32,
+    /// Stays at the same height while set
+    pub is_frozen: bool,
 }
 
 impl Goo {
-    pub fn new(start_time: f64) -> Goo {
+    pub fn new(start_time: f64, settings: &GooSettings) -> Goo {
         Goo {
-            y: GOO_INITIAL_POS,
+            y: settings.start,
             start_time,
+            risen: 0.0,
             regress: 0.0,
+            is_frozen: false,
         }
     }
+
+    /// Surface height
+    pub fn y(&self) -> f32 {
+        self.y
+    }
 }
 
-pub fn setup_goo(mut commands: Commands, time: Res<Time>) {
+pub fn setup_goo(
+    mut commands: Commands,
+    tuning: Res<TuningRes>,
+    goo_settings: Res<GooSettingsRes>,
+    physics_time: Res<PhysicsTime>,
+    goo_query: Query<(), With<Goo>>,
+) {
+    // Resuming from the pause menu keeps the current goo
+    if !goo_query.is_empty() {
+        return;
+    }
+
+    let settings = goo_settings.get(&tuning.value);
+
     commands
         .spawn_bundle(SpriteBundle {
             sprite: Sprite {
-                color: Color::BLACK,
+                color: settings.color,
                 custom_size: Some(Vec2::new(1280.0, 720.0)),
                 ..Default::default()
             },
             ..Default::default()
         })
-        .insert(Goo::new(time.seconds_since_startup()))
+        .insert(Goo::new(physics_time.elapsed(), &settings))
         .insert(GameStateEntity);
 }
 
+/// Start again from the bottom when the next level spawns
+pub fn reset_goo(
+    tuning: Res<TuningRes>,
+    goo_settings: Res<GooSettingsRes>,
+    physics_time: Res<PhysicsTime>,
+    mut level_events: EventReader<LevelEvent>,
+    mut goo_query: Query<&mut Goo>,
+) {
+    for event in level_events.iter() {
+        if let LevelEvent::Transformed(_) = event {
+            let settings = goo_settings.get(&tuning.value);
+
+            for mut goo in goo_query.iter_mut() {
+                *goo = Goo::new(physics_time.elapsed(), &settings);
+            }
+        }
+    }
+}
+
 pub fn goo_movement(
-    time: Res<Time>,
+    tuning: Res<TuningRes>,
+    goo_settings: Res<GooSettingsRes>,
+    physics_time: Res<PhysicsTime>,
     cameras: Query<&Transform, (With<GameCamera>, Without<Goo>)>,
-    mut goo_query: Query<(&mut Goo, &mut Transform, &Sprite)>,
+    player_query: Query<&Position, With<Player>>,
+    mut goo_query: Query<(&mut Goo, &mut Transform, &mut Sprite)>,
 ) {
     let camera_position = cameras.single();
-    let (mut goo, mut transform, sprite) = goo_query.single_mut();
+    let (mut goo, mut transform, mut sprite) = goo_query.single_mut();
+    let settings = goo_settings.get(&tuning.value);
+
+    if goo.is_frozen {
+        goo.start_time += PHYSICS_TIMESTEP;
+    } else {
+        let time = physics_time.elapsed() - goo.start_time;
+        let distance = player_query
+            .get_single()
+            .map_or(0.0, |position| position.value.y - goo.y);
+
+        goo.risen += settings.get_speed(time, distance) * PHYSICS_TIMESTEP as f32;
+    }
 
-    // goo.y = GOO_INITIAL_POS;
-    goo.y = GOO_INITIAL_POS - goo.regress
-        + (time.seconds_since_startup() - goo.start_time) as f32 * GOO_SPEED
-        + ((time.seconds_since_startup() * 2.0).sin() as f32) * GOO_SIN_AMPLITUDE;
+    goo.y = settings.start - goo.regress + goo.risen + settings.get_wave(physics_time.elapsed());
 
     transform.translation.x = camera_position.translation.x;
     transform.translation.y = goo.y - sprite.custom_size.unwrap().y / 2.0;
     transform.translation.z = 500.0;
+    sprite.color = settings.color;
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn goo_collision(
-    time: Res<Time>,
+    stats: Res<StatsRes>,
+    tuning: Res<TuningRes>,
+    physics_time: Res<PhysicsTime>,
     obstacles: Res<ObstaclesRes>,
-    mut player_query: Query<(&Position, &mut Player), (With<Player>, Without<Goo>)>,
+    mut player_query: Query<
+        (&mut Position, &mut Velocity, &mut Player),
+        (With<Player>, Without<Goo>),
+    >,
     mut app_state: ResMut<State<GameState>>,
     mut goo_query: Query<&mut Goo>,
+    mut goo_hit_event: EventWriter<GooHitEvent>,
+    mut family_rescue_event: EventWriter<FamilyRescueEvent>,
 ) {
     let mut goo = goo_query.single_mut();
-    let (player_position, mut player) = player_query.single_mut();
+    let (mut player_position, mut velocity, mut player) = player_query.single_mut();
+    let elapsed = physics_time.elapsed();
 
-    if player_position.value.y < goo.y {
-        player.lifes -= 1;
+    // `lifes > 0`: several physics steps can run before the state changes
+    if player_position.value.y < goo.y && player.lifes > 0 && !player.is_invulnerable(elapsed) {
+        if player.family_rescues > 0 {
+            // Caught by the family and thrown back up, no life lost
+            player.family_rescues -= 1;
+            velocity.y = stats.value.family_rescue_force;
+
+            family_rescue_event.send(FamilyRescueEvent {
+                position: player_position.value,
+            });
+        } else {
+            player.lifes -= 1;
+
+            goo_hit_event.send(GooHitEvent);
+
+            match player.checkpoint {
+                Some(checkpoint) if player.lifes > 0 => {
+                    player_position.teleport(checkpoint);
+                    *velocity = Velocity { x: 0.0, y: 0.0 };
+                }
+                _ => {}
+            }
+        }
 
         if player.lifes == 0 {
             app_state.set(GameState::LoseMenu).unwrap();
         } else {
-            let first_down_obstacle_tile_pos = get_first_obstacle_pos_downward(
+            // Below the floor under the player, there may be none
+            let floor_y = get_first_obstacle_pos_downward(
                 &obstacles.map,
                 to_tile_space(&player_position.value),
             )
-            .unwrap();
-
-            let floor_y = first_down_obstacle_tile_pos.1 as f32 * TILE_SIZE + TILE_SIZE;
-            let distance_to_floor = goo.y - floor_y;
+            .map_or(goo.y, |Point(_, y)| y as f32 * TILE_SIZE + TILE_SIZE);
 
-            player.blink(time.seconds_since_startup(), None);
-            goo.regress += distance_to_floor + GOO_HIT_REGRESS;
+            player.make_invulnerable(elapsed);
+            goo.regress += (goo.y - floor_y).max(0.0) + tuning.value.goo_hit_regress;
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_get_speed() {
+        let mut settings = GooSettings::from_tuning(&Tuning::default());
+
+        settings.speed = 30.0;
+        settings.catch_up_distance = 0.0;
+        assert_eq!(settings.get_speed(10.0, 1000.0), 30.0);
+
+        settings.curve = GooCurve::Accelerating(2.0);
+        assert_eq!(settings.get_speed(10.0, 0.0), 50.0);
+
+        settings.curve = GooCurve::Pausing {
+            every: 5.0,
+            pause: 2.0,
+        };
+        assert_eq!(settings.get_speed(4.0, 0.0), 30.0);
+        assert_eq!(settings.get_speed(6.0, 0.0), 0.0);
+        assert_eq!(settings.get_speed(8.0, 0.0), 30.0);
+
+        // Full catch up from twice the distance
+        settings.curve = GooCurve::Constant;
+        settings.catch_up_distance = 100.0;
+        settings.catch_up_speed = 40.0;
+        assert_eq!(settings.get_speed(0.0, 100.0), 30.0);
+        assert_eq!(settings.get_speed(0.0, 150.0), 50.0);
+        assert_eq!(settings.get_speed(0.0, 400.0), 70.0);
+    }
+}
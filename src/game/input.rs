@@ -1,49 +1,106 @@
 use bevy::prelude::*;
 
-use crate::{stats::StatsRes, GameState};
+use crate::{
+    controls::{Action, ActionsRes},
+    stats::StatsRes,
+    tuning::TuningRes,
+    GameState,
+};
 
 use super::{
-    bouncer,
-    events::{DirectionChangeEvent, JumpEvent},
-    Player, PlayerDirection, Position, Velocity,
+    events::{DirectionChangeEvent, JumpEvent, WallJumpEvent},
+    physics::{PhysicsTime, PHYSICS_TIMESTEP},
+    player::{is_on_one_way_platform, PLAYER_HEIGHT_HALF},
+    psychosis::Hallucination,
+    ObstaclesRes, Player, PlayerDirection, Position, Velocity,
 };
 
-const COYOTE_TIME: f64 = 0.125; // seconds after falling from a platform that still can jump
+/// Actions sampled every frame, consumed by the physics steps
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PlayerInput {
+    pub left: bool,
+    pub right: bool,
+    pub jump: bool,
+    pub jump_held: bool,
+    /// With Jump, drops through one-way platforms
+    pub down: bool,
+}
+
+pub fn read_input(
+    actions: Res<ActionsRes>,
+    mut input: ResMut<PlayerInput>,
+    mut app_state: ResMut<State<GameState>>,
+) {
+    input.left = actions.pressed(Action::MoveLeft);
+    input.right = actions.pressed(Action::MoveRight);
+    input.down = actions.pressed(Action::MoveDown);
+    // Keep the press until a physics step handles it, frames without steps would lose it
+    input.jump |= actions.just_pressed(Action::Jump);
+    input.jump_held = actions.pressed(Action::Jump);
+
+    if actions.just_pressed(Action::Restart) {
+        app_state.set(GameState::MainMenu).unwrap();
+    }
+}
 
 pub fn handle_input(
     stats: Res<StatsRes>,
-    time: Res<Time>,
-    keys: Res<Input<KeyCode>>,
+    tuning: Res<TuningRes>,
+    physics_time: Res<PhysicsTime>,
+    obstacles: Res<ObstaclesRes>,
+    mut input: ResMut<PlayerInput>,
     mut jump_event: EventWriter<JumpEvent>,
     mut direction_change_event: EventWriter<DirectionChangeEvent>,
-    mut app_state: ResMut<State<GameState>>,
+    mut wall_jump_event: EventWriter<WallJumpEvent>,
     mut player_query: Query<(&mut Velocity, &mut Player, &Position), With<Player>>,
 ) {
     let (mut velocity, mut player, position) = player_query.single_mut();
-    let time_delta = time.delta_seconds();
+    let time_delta = PHYSICS_TIMESTEP as f32;
+    let jump_pressed = std::mem::take(&mut input.jump);
     let top_speed;
     let top_speed_rate;
     let stop_rate;
     let jump_force;
+    let jump_min_force;
+    let wall_jump_force;
+    let wall_jump_kick;
 
-    if player.depressed_until > time.seconds_since_startup() {
+    if player.depressed_until > physics_time.elapsed() {
         top_speed = stats.value.top_speed_depressed;
         top_speed_rate = stats.value.top_speed_rate_depressed;
         stop_rate = stats.value.stop_rate_depressed;
         jump_force = stats.value.jump_force_depressed;
+        jump_min_force = stats.value.jump_min_force_depressed;
+        wall_jump_force = stats.value.wall_jump_force_depressed;
+        wall_jump_kick = stats.value.wall_jump_kick_depressed;
     } else {
         top_speed = stats.value.top_speed;
         top_speed_rate = stats.value.top_speed_rate;
         stop_rate = stats.value.stop_rate;
         jump_force = stats.value.jump_force;
+        jump_min_force = stats.value.jump_min_force;
+        wall_jump_force = stats.value.wall_jump_force;
+        wall_jump_kick = stats.value.wall_jump_kick;
     }
 
-    if let Some(last_ground_time) = player.last_ground_time {
+    let is_dropping_through = jump_pressed
+        && input.down
+        && player.last_ground_time.is_some()
+        && stats.value.can_drop_through_platforms
+        && is_on_one_way_platform(&position.value, &obstacles);
+
+    if is_dropping_through {
+        player.drop_until = physics_time.elapsed() + tuning.value.drop_through_time;
+        player.drop_y = position.value.y - PLAYER_HEIGHT_HALF;
+        player.last_ground_time = None;
+        player.buffer_jump_time = None;
+    } else if let Some(last_ground_time) = player.last_ground_time {
         // Player is in the ground
-        let is_in_jump_window = time.seconds_since_startup() < last_ground_time + COYOTE_TIME;
-        let is_buffered_jump_valid = player.is_buffered_jump_valid(time.seconds_since_startup());
-        let can_jump =
-            keys.just_pressed(KeyCode::Space) && is_in_jump_window || is_buffered_jump_valid;
+        let is_in_jump_window =
+            physics_time.elapsed() < last_ground_time + tuning.value.coyote_time;
+        let is_buffered_jump_valid =
+            player.is_buffered_jump_valid(physics_time.elapsed(), tuning.value.jump_buffer_time);
+        let can_jump = jump_pressed && is_in_jump_window || is_buffered_jump_valid;
 
         if !is_in_jump_window {
             player.last_ground_time = None;
@@ -54,6 +111,8 @@ pub fn handle_input(
 
             velocity.y = jump_force;
             player.last_ground_time = None;
+            player.jump_force = Some(jump_force);
+            player.jump_min_force = Some(jump_min_force);
 
             jump_event.send(JumpEvent {
                 is_grounded,
@@ -64,16 +123,54 @@ pub fn handle_input(
 
         // Clear buffered jump time
         player.buffer_jump_time = None;
-    } else if keys.just_pressed(KeyCode::Space) {
-        // Player is in the air AND "space" is pressed
-        player.buffer_jump_time = Some(time.seconds_since_startup());
+    } else if jump_pressed && player.wall_contact.is_some() {
+        // Player is in the air against a wall, jump away from it
+        let direction = if player.wall_contact == Some(PlayerDirection::Left) {
+            PlayerDirection::Right
+        } else {
+            PlayerDirection::Left
+        };
+
+        velocity.y = wall_jump_force;
+        velocity.x = if direction == PlayerDirection::Right {
+            wall_jump_kick
+        } else {
+            -wall_jump_kick
+        };
+        player.direction = direction;
+        player.wall_contact = None;
+        player.jump_force = Some(wall_jump_force);
+        player.jump_min_force = Some(jump_min_force.min(wall_jump_force));
+
+        wall_jump_event.send(WallJumpEvent {
+            position: position.value,
+            velocity: Vec2::new(velocity.x, velocity.y),
+        });
+    } else if jump_pressed {
+        // Player is in the air AND jump is pressed
+        player.buffer_jump_time = Some(physics_time.elapsed());
     }
 
-    if keys.just_pressed(KeyCode::R) {
-        app_state.set(GameState::MainMenu).unwrap();
+    // Releasing Jump while going up cuts the jump short
+    if let Some(jump_min_force) = player.jump_min_force {
+        if velocity.y <= 0.0 {
+            player.jump_min_force = None;
+        } else if !input.jump_held && velocity.y > jump_min_force {
+            velocity.y = jump_min_force;
+            player.jump_force = Some(jump_min_force);
+            player.jump_min_force = None;
+        }
     }
 
-    if keys.pressed(KeyCode::Left) {
+    let (left, right) = if player.get_hallucination(physics_time.elapsed())
+        == Some(Hallucination::FlippedControls)
+    {
+        (input.right, input.left)
+    } else {
+        (input.left, input.right)
+    };
+
+    if left {
         if player.direction != PlayerDirection::Left && velocity.y == 0.0 {
             direction_change_event.send(DirectionChangeEvent {
                 position: position.value,
@@ -83,7 +180,7 @@ pub fn handle_input(
 
         player.direction = PlayerDirection::Left;
         velocity.x = (velocity.x - top_speed_rate * time_delta).max(-top_speed);
-    } else if keys.pressed(KeyCode::Right) {
+    } else if right {
         if player.direction != PlayerDirection::Right && velocity.y == 0.0 {
             direction_change_event.send(DirectionChangeEvent {
                 position: position.value,
@@ -101,14 +198,14 @@ pub fn handle_input(
 
     // Apply bounce force
     if let Some(mut bounce_force) = player.bounce_force {
+        let bounce_stop_rate = tuning.value.bouncer_force / tuning.value.bouncer_duration;
+
         velocity.x += bounce_force * time_delta;
 
         bounce_force = if bounce_force > 0.0 {
-            (bounce_force - (bouncer::BOUNCER_FORCE / bouncer::BOUNCER_DURATION) * time_delta)
-                .max(0.0)
+            (bounce_force - bounce_stop_rate * time_delta).max(0.0)
         } else if bounce_force < 0.0 {
-            (bounce_force + (bouncer::BOUNCER_FORCE / bouncer::BOUNCER_DURATION) * time_delta)
-                .min(0.0)
+            (bounce_force + bounce_stop_rate * time_delta).min(0.0)
         } else {
             0.0
         };
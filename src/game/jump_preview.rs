@@ -0,0 +1,139 @@
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+use crate::stats::StatsRes;
+
+use super::{
+    obstacles::{to_tile_space, Obstacle, Point},
+    physics::{PhysicsTime, PHYSICS_TIMESTEP},
+    player::PLAYER_HEIGHT_HALF,
+    GameStateEntity, ObstaclesRes, Player, Position, Velocity, GRAVITY,
+};
+
+const JUMP_PREVIEW_DOTS: usize = 8;
+const DOT_SIZE: f32 = 2.0;
+const DOT_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.7);
+
+/// Arc of a full jump from where the player stands, for the characters smart enough to see it
+#[derive(Component)]
+pub struct JumpPreviewDot(usize);
+
+/// Feet positions on a full jump from `feet`, one per physics step,
+/// until `duration` or until going into a solid tile
+pub fn get_jump_trajectory(
+    feet: Vec2,
+    velocity: Vec2,
+    duration: f32,
+    obstacles: &HashMap<Point<i32>, Obstacle>,
+) -> Vec<Vec2> {
+    let time_delta = PHYSICS_TIMESTEP as f32;
+    let steps = (duration / time_delta).round() as usize;
+    let mut position = feet;
+    let mut velocity = velocity;
+    let mut points = vec![];
+
+    for _ in 0..steps {
+        velocity.y += GRAVITY * time_delta;
+        position += velocity * time_delta;
+
+        if let Some(obstacle) = obstacles.get(&to_tile_space(&position)) {
+            if !obstacle.is_one_way {
+                break;
+            }
+        }
+
+        points.push(position);
+    }
+
+    points
+}
+
+pub fn setup_jump_preview(mut commands: Commands) {
+    for index in 0..JUMP_PREVIEW_DOTS {
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: DOT_COLOR,
+                    custom_size: Some(Vec2::splat(DOT_SIZE)),
+                    ..Default::default()
+                },
+                visibility: Visibility { is_visible: false },
+                ..Default::default()
+            })
+            .insert(JumpPreviewDot(index))
+            .insert(GameStateEntity);
+    }
+}
+
+pub fn show_jump_preview(
+    stats: Res<StatsRes>,
+    physics_time: Res<PhysicsTime>,
+    obstacles: Res<ObstaclesRes>,
+    player_query: Query<(&Player, &Position, &Velocity)>,
+    mut dots: Query<(&JumpPreviewDot, &mut Transform, &mut Visibility)>,
+) {
+    let (player, position, velocity) = player_query.single();
+    let is_grounded = player.last_ground_time.is_some() && velocity.y == 0.0;
+    let mut points = vec![];
+
+    if is_grounded && stats.value.jump_preview_time > 0.0 {
+        let jump_force = if player.depressed_until > physics_time.elapsed() {
+            stats.value.jump_force_depressed
+        } else {
+            stats.value.jump_force
+        };
+        let render_position = position
+            .previous
+            .lerp(position.value, physics_time.overstep_percentage());
+
+        points = get_jump_trajectory(
+            render_position - Vec2::new(0.0, PLAYER_HEIGHT_HALF),
+            Vec2::new(velocity.x, jump_force),
+            stats.value.jump_preview_time,
+            &obstacles.map,
+        );
+    }
+
+    for (JumpPreviewDot(index), mut transform, mut visibility) in dots.iter_mut() {
+        // Spread along the arc, the last dot at its end
+        let point = (((index + 1) * points.len()) / JUMP_PREVIEW_DOTS)
+            .checked_sub(1)
+            .and_then(|point| points.get(point));
+
+        visibility.is_visible = point.is_some();
+
+        if let Some(point) = point {
+            transform.translation = point.extend(20.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game::TILE_SIZE;
+
+    #[test]
+    fn test_get_jump_trajectory() {
+        let velocity = Vec2::new(100.0, 400.0);
+        let points = get_jump_trajectory(Vec2::ZERO, velocity, 1.0, &HashMap::new());
+        let apex = points.iter().map(|point| point.y).fold(0.0, f32::max);
+        let height = velocity.y * velocity.y / (-2.0 * GRAVITY);
+
+        assert_eq!(points.len(), 60);
+        assert!((apex - height).abs() < 4.0, "{apex} {height}");
+        assert!(points.last().unwrap().x > 99.0);
+
+        // Cut by a wall two tiles to the right
+        let obstacles = HashMap::from([(
+            Point(2, 0),
+            Obstacle {
+                pos: Point(2, 0),
+                is_one_way: false,
+            },
+        )]);
+        let points = get_jump_trajectory(Vec2::ZERO, Vec2::new(200.0, 200.0), 1.0, &obstacles);
+
+        assert!(points.last().unwrap().x < 2.0 * TILE_SIZE);
+    }
+}
@@ -1,14 +1,27 @@
 mod bouncer;
 mod camera;
+pub mod campaign;
+mod checkpoint;
+mod debug;
 mod depression;
+#[cfg(feature = "dev")]
+mod dev;
 mod events;
 mod goal;
-mod goo;
-mod input;
+pub mod goo;
+pub mod input;
+mod jump_preview;
 mod obstacles;
+pub mod physics;
+mod platforms;
 mod player;
+mod psychosis;
+pub mod replay;
+pub mod rule;
+pub mod run_stats;
 mod setup;
 mod sfx;
+pub mod validation;
 mod vfx;
 
 use benimator::SpriteSheetAnimation;
@@ -18,86 +31,212 @@ use std::collections::HashMap;
 
 use crate::{
     stats::{Intelligence, MentalHealth, SkinColor, Stats, StatsRes, Strength, Wealth},
+    tuning::{Tuning, TuningRes},
     utils::clean_state,
     GameState,
 };
 
-use self::obstacles::{Obstacle, Point};
+use self::{
+    obstacles::{Obstacle, PlatformObstacle, Point},
+    physics::{PhysicsStage, PhysicsTime},
+    psychosis::Hallucination,
+};
 
 // CONSTANTS
 pub const TILE_SIZE: f32 = 16.0;
 pub const PLAYER_WIDTH: f32 = 16.0;
 pub const PLAYER_HEIGHT: f32 = 36.0;
 pub const GRAVITY: f32 = -1422.0;
-const JUMP_BUFFER_TIME: f64 = 0.1; // seconds before touching ground that jump will be valid
 
 pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
         app.add_plugin(LdtkPlugin)
-            .add_event::<events::JumpEvent>()
+            .add_plugin(LdtkObjectsPlugin)
+            .add_plugin(GameplayPlugin)
+            .add_plugin(debug::DebugPlugin);
+
+        #[cfg(feature = "dev")]
+        app.add_plugin(dev::DevPlugin);
+    }
+}
+
+/// The bundles spawned for the level int grid values, shared with the headless harness
+pub struct LdtkObjectsPlugin;
+
+impl Plugin for LdtkObjectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_ldtk_int_cell::<WallBundle>(1)
+            .register_ldtk_int_cell::<WallBundle>(2)
+            .register_ldtk_int_cell::<OneWayPlatformBundle>(3);
+    }
+}
+
+/// Game resources and systems without the `LdtkPlugin`, which needs rendering,
+/// so they can also run headless
+pub struct GameplayPlugin;
+
+impl Plugin for GameplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<events::JumpEvent>()
             .add_event::<events::LandingEvent>()
             .add_event::<events::CeilHitEvent>()
+            .add_event::<events::WallSlideEvent>()
+            .add_event::<events::WallJumpEvent>()
+            .add_event::<events::GooHitEvent>()
+            .add_event::<events::FamilyRescueEvent>()
+            .add_event::<events::BounceEvent>()
+            .add_event::<events::DepressionEvent>()
+            .add_event::<events::PsychosisEvent>()
             .add_event::<events::DirectionChangeEvent>()
             .init_resource::<Animations>()
+            .init_resource::<campaign::CampaignRes>()
+            .init_resource::<debug::CollisionDebugRes>()
+            .init_resource::<goo::GooSettingsRes>()
+            .init_resource::<input::PlayerInput>()
+            .init_resource::<PhysicsTime>()
+            .init_resource::<replay::ReplayRes>()
+            .init_resource::<run_stats::RunStatsRes>()
+            .init_resource::<TuningRes>()
+            .init_resource::<validation::LevelProblemsRes>()
             .insert_resource(PlayerPositionsRes { value: vec![] })
-            .insert_resource(StatsRes {
-                value: Stats::from_config(
-                    SkinColor::Light,
-                    MentalHealth::Healthy,
-                    true,
-                    Intelligence::Smart,
-                    true,
-                    Strength::Weak,
-                    Wealth::Rich,
-                ),
-            })
+            .insert_resource(StatsRes::new(Stats::from_config(
+                &Tuning::default(),
+                SkinColor::Light,
+                MentalHealth::Healthy,
+                true,
+                Intelligence::Smart,
+                true,
+                Strength::Weak,
+                Wealth::Rich,
+            )))
             .insert_resource(LevelSelection::Index(0))
             .insert_resource(ObstaclesRes {
                 map: HashMap::new(),
+                platforms: vec![],
             })
-            .register_ldtk_int_cell::<WallBundle>(1)
-            .register_ldtk_int_cell::<WallBundle>(2)
-            .register_ldtk_int_cell::<OneWayPlatformBundle>(3)
             .add_system_set(
                 SystemSet::on_enter(GameState::Game)
+                    .with_system(physics::reset_physics_time)
+                    .with_system(run_stats::reset_run_stats)
+                    .with_system(campaign::setup_campaign)
+                    .with_system(replay::apply_replay_stats.before(setup::setup_game))
+                    .with_system(
+                        setup::apply_tuning
+                            .after(replay::apply_replay_stats)
+                            .before(setup::setup_game),
+                    )
                     .with_system(setup::setup_animations.before(setup::setup_game))
                     .with_system(setup::setup_game)
+                    .with_system(jump_preview::setup_jump_preview)
                     .with_system(setup::show_character_menu.after(setup::setup_game)),
             )
             .add_system_set(
-                // Runs while showing character menu (extra bg setup)
-                SystemSet::on_inactive_update(GameState::Game)
+                // Runs while showing character menu (extra bg setup) and when changing level
+                SystemSet::new()
+                    .with_run_criteria(setup::is_game_in_stack)
                     .with_system(setup::setup_obstacles)
-                    .with_system(setup::setup_entities),
+                    .with_system(setup::setup_entities)
+                    .with_system(campaign::level_loaded)
+                    .with_system(goo::reset_goo.after(setup::setup_entities)),
+            )
+            .add_system_set(
+                SystemSet::on_resume(GameState::Game)
+                    .with_system(goo::setup_goo)
+                    .with_system(replay::start_replay),
             )
-            .add_system_set(SystemSet::on_resume(GameState::Game).with_system(goo::setup_goo))
             .add_system_set(
                 SystemSet::on_update(GameState::Game)
                     .with_system(player::player_color)
-                    .with_system(depression::show_depressed_text.after(player::player_color))
-                    .with_system(input::handle_input.after(player::player_color))
-                    .with_system(player::player_movement.after(input::handle_input))
-                    .with_system(player::player_animation.after(player::player_movement))
-                    .with_system(vfx::add_ceil_hit_sprite.after(player::player_movement))
-                    .with_system(vfx::add_direction_change_dust.after(player::player_movement))
-                    .with_system(vfx::add_jump_dust.after(player::player_movement))
-                    .with_system(vfx::add_landing_dust.after(player::player_movement))
+                    .with_system(
+                        player::show_player_text::<DepressedText>.after(player::player_color),
+                    )
+                    .with_system(
+                        player::show_player_text::<PsychoticText>.after(player::player_color),
+                    )
+                    .with_system(input::read_input)
+                    .with_system(player::player_animation)
+                    .with_system(jump_preview::show_jump_preview)
+                    .with_system(platforms::platform_sprite)
+                    .with_system(vfx::add_ceil_hit_sprite)
+                    .with_system(vfx::add_direction_change_dust)
+                    .with_system(vfx::add_jump_dust)
+                    .with_system(vfx::add_landing_dust)
+                    .with_system(vfx::add_wall_slide_dust)
+                    .with_system(vfx::add_wall_jump_dust)
+                    .with_system(vfx::add_family_rescue_sprite)
                     .with_system(vfx::blink_player)
                     .with_system(vfx::remove_vfx)
-                    .with_system(sfx::play_ceil_hit_sound.after(player::player_movement))
-                    .with_system(sfx::play_jump_sound.after(player::player_movement))
-                    .with_system(sfx::play_landing_sound.after(player::player_movement))
-                    .with_system(camera::camera_movement.after(player::player_movement))
+                    .with_system(sfx::play_ceil_hit_sound)
+                    .with_system(sfx::play_jump_sound)
+                    .with_system(sfx::play_wall_jump_sound)
+                    .with_system(sfx::play_landing_sound)
+                    .with_system(sfx::play_family_rescue_sound)
+                    .with_system(camera::camera_movement)
+                    .with_system(psychosis::distort_view),
+            )
+            // Gameplay runs at a fixed rate, after `Update` so the state is settled
+            .add_stage_after(
+                CoreStage::Update,
+                PhysicsStage,
+                SystemStage::parallel()
+                    .with_run_criteria(physics::physics_timestep)
+                    .with_system(player::save_previous_position)
+                    .with_system(replay::replay_input)
+                    .with_system(platforms::move_platforms)
+                    .with_system(
+                        platforms::crumble_platforms
+                            .after(platforms::move_platforms)
+                            .after(player::save_previous_position),
+                    )
+                    .with_system(
+                        platforms::carry_player
+                            .after(platforms::crumble_platforms)
+                            .before(input::handle_input),
+                    )
+                    .with_system(
+                        platforms::update_platform_obstacles
+                            .after(platforms::carry_player)
+                            .before(input::handle_input),
+                    )
+                    .with_system(
+                        input::handle_input
+                            .after(player::save_previous_position)
+                            .after(replay::replay_input),
+                    )
+                    .with_system(player::player_movement.after(input::handle_input))
+                    .with_system(bouncer::bounce_player.after(player::player_movement))
                     .with_system(goo::goo_movement)
-                    .with_system(goo::goo_collision.after(goo::goo_movement).after(player::player_movement))
-                    .with_system(goal::goal_collision.after(player::player_movement))
-                    .with_system(depression::trigger_depression)
-                    .with_system(bouncer::bounce_player),
+                    .with_system(
+                        goo::goo_collision
+                            .after(goo::goo_movement)
+                            .after(bouncer::bounce_player),
+                    )
+                    .with_system(goal::goal_collision.after(bouncer::bounce_player))
+                    .with_system(
+                        checkpoint::checkpoint_collision
+                            .after(bouncer::bounce_player)
+                            .before(goo::goo_collision),
+                    )
+                    .with_system(depression::trigger_depression.before(input::handle_input))
+                    .with_system(psychosis::trigger_psychosis.before(input::handle_input))
+                    .with_system(
+                        psychosis::add_hallucinated_platforms.after(psychosis::trigger_psychosis),
+                    )
+                    .with_system(psychosis::remove_hallucinated_platforms)
+                    .with_system(
+                        run_stats::track_run
+                            .after(goo::goo_collision)
+                            .after(goal::goal_collision)
+                            .after(depression::trigger_depression)
+                            .after(psychosis::trigger_psychosis),
+                    ),
             )
             .add_system_set(
-                SystemSet::on_exit(GameState::Game).with_system(clean_state::<GameStateEntity>),
+                SystemSet::on_exit(GameState::Game)
+                    .with_system(clean_state::<GameStateEntity>)
+                    .with_system(replay::save_replay),
             );
     }
 }
@@ -109,6 +248,7 @@ pub struct Animations {
     idle: Handle<SpriteSheetAnimation>,
     run: Handle<SpriteSheetAnimation>,
     jump: Vec<Handle<SpriteSheetAnimation>>,
+    wall_slide: Handle<SpriteSheetAnimation>,
     // VFX
     vfx_atlas: Handle<TextureAtlas>,
     vfx_debug: Handle<SpriteSheetAnimation>,
@@ -119,6 +259,7 @@ pub struct Animations {
 
 pub struct ObstaclesRes {
     map: HashMap<Point<i32>, Obstacle>,
+    platforms: Vec<PlatformObstacle>,
 }
 
 #[derive(Debug)]
@@ -130,32 +271,76 @@ pub struct PlayerPositionsRes {
 #[derive(Component)]
 struct GameStateEntity;
 
-#[derive(Debug, PartialEq)]
+/// Despawned when the next level spawns
+#[derive(Component)]
+struct LevelEntity;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "dev", derive(bevy_inspector_egui::Inspectable))]
 pub enum PlayerDirection {
     Left,
     Right,
 }
 
 #[derive(Component)]
+#[cfg_attr(feature = "dev", derive(bevy_inspector_egui::Inspectable))]
 pub struct Player {
     direction: PlayerDirection,
     depressed_until: f64,
+    psychotic_until: f64,
+    /// What the current psychotic episode makes the player see
+    hallucination: Hallucination,
     blink_until: f64,
-    lifes: i32,
+    /// The goo can't take another life until then
+    invulnerable_until: f64,
+    /// The one-way platform dropped through with Down+Jump is ignored until then
+    drop_until: f64,
+    /// Top of the platform dropped through, the ones below still hold
+    drop_y: f32,
+    pub lifes: i32,
+    /// Respawn position of the last checkpoint touched in this level
+    checkpoint: Option<Vec2>,
+    /// Left for this run, see `Stats.family_rescues`
+    family_rescues: u32,
     bounce_force: Option<f32>,
     last_ground_time: Option<f64>,
     buffer_jump_time: Option<f64>,
+    /// Launch force of the current jump, lowered if it's cut short
+    jump_force: Option<f32>,
+    /// Set while releasing Jump can still cut the jump short
+    jump_min_force: Option<f32>,
+    /// Side of the wall the player is pushing against
+    wall_contact: Option<PlayerDirection>,
+    is_wall_sliding: bool,
 }
 
 impl Player {
-    fn blink(&mut self, seconds_since_startup: f64, blink_duration_secs: Option<f64>) {
+    fn blink(&mut self, elapsed: f64, blink_duration_secs: Option<f64>) {
         let blink_duration_secs = blink_duration_secs.unwrap_or(1.5);
-        self.blink_until = seconds_since_startup + blink_duration_secs;
+        self.blink_until = elapsed + blink_duration_secs;
     }
 
-    fn is_buffered_jump_valid(&self, seconds_since_startup: f64) -> bool {
+    /// Blinks for as long as the goo can't take another life
+    fn make_invulnerable(&mut self, elapsed: f64) {
+        self.blink(elapsed, None);
+        self.invulnerable_until = self.blink_until;
+    }
+
+    fn is_invulnerable(&self, elapsed: f64) -> bool {
+        self.invulnerable_until > elapsed
+    }
+
+    fn get_hallucination(&self, elapsed: f64) -> Option<Hallucination> {
+        if self.psychotic_until > elapsed {
+            Some(self.hallucination)
+        } else {
+            None
+        }
+    }
+
+    fn is_buffered_jump_valid(&self, elapsed: f64, jump_buffer_time: f64) -> bool {
         if let Some(buffer_jump_time) = self.buffer_jump_time {
-            (seconds_since_startup - buffer_jump_time) < JUMP_BUFFER_TIME
+            (elapsed - buffer_jump_time) < jump_buffer_time
         } else {
             false
         }
@@ -165,9 +350,27 @@ impl Player {
 #[derive(Component)]
 pub struct Position {
     pub value: Vec2,
+    /// Position at the previous physics step, to interpolate rendering
+    pub previous: Vec2,
+}
+
+impl Position {
+    pub fn new(value: Vec2) -> Position {
+        Position {
+            value,
+            previous: value,
+        }
+    }
+
+    /// Move without interpolating from the previous position
+    pub fn teleport(&mut self, value: Vec2) {
+        self.value = value;
+        self.previous = value;
+    }
 }
 
 #[derive(Component)]
+#[cfg_attr(feature = "dev", derive(bevy_inspector_egui::Inspectable))]
 pub struct Velocity {
     pub x: f32,
     pub y: f32,
@@ -194,3 +397,30 @@ pub struct LifesText;
 
 #[derive(Component)]
 pub struct DepressedText;
+
+#[derive(Component)]
+pub struct PsychoticText;
+
+/// Text shown over the player during an episode, see `player::show_player_text`
+pub trait PlayerText: Component {
+    /// Height above the player center
+    const OFFSET_Y: f32;
+
+    fn is_visible(player: &Player, elapsed: f64) -> bool;
+}
+
+impl PlayerText for DepressedText {
+    const OFFSET_Y: f32 = 24.0;
+
+    fn is_visible(player: &Player, elapsed: f64) -> bool {
+        player.depressed_until > elapsed
+    }
+}
+
+impl PlayerText for PsychoticText {
+    const OFFSET_Y: f32 = 36.0;
+
+    fn is_visible(player: &Player, elapsed: f64) -> bool {
+        player.psychotic_until > elapsed
+    }
+}
@@ -9,15 +9,23 @@ pub struct Obstacle {
     pub is_one_way: bool,
 }
 
+/// Active platform, see `platforms::update_platform_obstacles`
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlatformObstacle {
+    /// Only the top surface of one-way platforms, the whole box of solid ones
+    pub bbox: BBox<f32>,
+    pub is_one_way: bool,
+}
+
 #[derive(Clone, Debug, PartialEq, Hash, Eq)]
 pub struct Point<T: Copy>(pub T, pub T);
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct BBox<T: Copy> {
-    left: T,
-    bottom: T,
-    right: T,
-    top: T,
+    pub left: T,
+    pub bottom: T,
+    pub right: T,
+    pub top: T,
 }
 
 impl<T: Copy> BBox<T> {
@@ -31,6 +39,16 @@ impl<T: Copy> BBox<T> {
     }
 }
 
+impl BBox<f32> {
+    /// Touching boxes overlap, like tiles in `get_tile_space_bbox`
+    pub fn overlaps(&self, other: &BBox<f32>) -> bool {
+        self.left <= other.right
+            && other.left <= self.right
+            && self.bottom <= other.top
+            && other.bottom <= self.top
+    }
+}
+
 /// Map screen-space bbox to tile-space bbox
 pub fn get_tile_space_bbox(bbox: &BBox<f32>) -> BBox<i32> {
     BBox::new(
@@ -62,6 +80,14 @@ pub fn to_tile_space(pos: &Vec2) -> Point<i32> {
     )
 }
 
+/// Screen-space bbox of a tile
+pub fn get_tile_bbox(tile: &Point<i32>) -> BBox<f32> {
+    let left = tile.0 as f32 * TILE_SIZE;
+    let bottom = tile.1 as f32 * TILE_SIZE;
+
+    BBox::new(left, bottom, left + TILE_SIZE, bottom + TILE_SIZE)
+}
+
 pub fn get_first_obstacle_pos_downward(
     obstacles: &HashMap<Point<i32>, Obstacle>,
     pos: Point<i32>,
@@ -96,6 +122,24 @@ pub fn get_obstacle_list(
         .collect::<Vec<_>>()
 }
 
+/// Screen-space bboxes of the tile `obstacles` and the `platforms` in `bbox`
+pub fn get_obstacle_bboxes(
+    bbox: &BBox<f32>,
+    obstacles: &[&Obstacle],
+    platforms: &[BBox<f32>],
+) -> Vec<BBox<f32>> {
+    obstacles
+        .iter()
+        .map(|obstacle| get_tile_bbox(&obstacle.pos))
+        .chain(
+            platforms
+                .iter()
+                .filter(|platform| platform.overlaps(bbox))
+                .cloned(),
+        )
+        .collect()
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -171,4 +215,29 @@ mod test {
         assert_eq!(list.len(), 1);
         assert_eq!(list[0].pos, Point(0, 1));
     }
+
+    #[test]
+    fn test_get_obstacle_bboxes() {
+        let wall = Obstacle {
+            pos: Point(1, 0),
+            is_one_way: false,
+        };
+        let platforms = [
+            BBox::new(0.0, 30.0, 48.0, 32.0),
+            BBox::new(0.0, 60.0, 48.0, 60.0),
+        ];
+
+        assert_eq!(
+            get_obstacle_bboxes(&BBox::new(20.0, 10.0, 30.0, 30.0), &[&wall], &platforms),
+            vec![
+                BBox::new(16.0, 0.0, 32.0, 16.0),
+                BBox::new(0.0, 30.0, 48.0, 32.0)
+            ]
+        );
+
+        assert_eq!(
+            get_obstacle_bboxes(&BBox::new(20.0, 33.0, 30.0, 59.0), &[], &platforms),
+            vec![]
+        );
+    }
 }
@@ -0,0 +1,158 @@
+use bevy::{ecs::schedule::ShouldRun, prelude::*};
+
+use crate::GameState;
+
+use super::campaign::CampaignRes;
+
+pub const PHYSICS_TIMESTEP: f64 = 1.0 / 60.0; // seconds per physics step
+/// Steps a frame can catch up on, the rest of a long frame (a window drag, a breakpoint) is dropped
+const MAX_FRAME_STEPS: f64 = 4.0;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, StageLabel)]
+pub struct PhysicsStage;
+
+/// Fixed timestep accumulator and clock for the `PhysicsStage`. It's the game clock:
+/// it only ticks while playing and, scaled, runs fewer or more steps per real second.
+pub struct PhysicsTime {
+    accumulator: f64,
+    looping: bool,
+    tick: u64,
+    lockstep: bool,
+    scale: f64,
+}
+
+impl Default for PhysicsTime {
+    fn default() -> Self {
+        PhysicsTime {
+            accumulator: 0.0,
+            looping: false,
+            tick: 0,
+            lockstep: false,
+            scale: 1.0,
+        }
+    }
+}
+
+impl PhysicsTime {
+    /// Run exactly one step per update, ignoring `Time`. For headless runs.
+    pub fn lockstep() -> PhysicsTime {
+        PhysicsTime {
+            lockstep: true,
+            ..Default::default()
+        }
+    }
+
+    /// Seconds of simulation since the run started, gameplay timers should use this
+    /// instead of `Time` so they are the same on every run of the same inputs
+    pub fn elapsed(&self) -> f64 {
+        self.tick as f64 * PHYSICS_TIMESTEP
+    }
+
+    #[cfg(feature = "dev")]
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Game speed, e.g. `0.5` for slow motion. Steps keep the same length so runs
+    /// (and replays) are the same at any speed.
+    #[cfg(any(test, feature = "dev"))]
+    pub fn set_scale(&mut self, scale: f64) {
+        self.scale = scale.max(0.0);
+    }
+
+    fn advance(&mut self, delta_seconds: f64) {
+        self.accumulator += if self.lockstep {
+            PHYSICS_TIMESTEP
+        } else {
+            delta_seconds * self.scale
+        };
+        self.accumulator = self.accumulator.min(MAX_FRAME_STEPS * PHYSICS_TIMESTEP);
+    }
+
+    /// How far (0..1) we are between the last step and the next one, used to interpolate rendering
+    pub fn overstep_percentage(&self) -> f32 {
+        (self.accumulator / PHYSICS_TIMESTEP) as f32
+    }
+}
+
+/// Runs the `PhysicsStage` as many times as fixed steps fit in the elapsed time.
+/// Only ticks while `GameState::Game` is the active state and the level has spawned,
+/// so there is no catch-up after the character menu or a level change.
+pub fn physics_timestep(
+    time: Res<Time>,
+    app_state: Res<State<GameState>>,
+    campaign: Res<CampaignRes>,
+    mut physics_time: ResMut<PhysicsTime>,
+) -> ShouldRun {
+    step_physics(
+        time.delta_seconds_f64(),
+        &app_state,
+        &campaign,
+        &mut physics_time,
+    )
+}
+
+/// `physics_timestep` for a frame of `delta_seconds`, so tests can choose the frame rate
+pub fn step_physics(
+    delta_seconds: f64,
+    app_state: &State<GameState>,
+    campaign: &CampaignRes,
+    physics_time: &mut PhysicsTime,
+) -> ShouldRun {
+    if *app_state.current() != GameState::Game || campaign.is_loading {
+        physics_time.accumulator = 0.0;
+        physics_time.looping = false;
+        return ShouldRun::No;
+    }
+
+    if !physics_time.looping {
+        physics_time.advance(delta_seconds);
+    }
+
+    if physics_time.accumulator >= PHYSICS_TIMESTEP {
+        physics_time.accumulator -= PHYSICS_TIMESTEP;
+        physics_time.looping = true;
+        physics_time.tick += 1;
+        ShouldRun::YesAndCheckAgain
+    } else {
+        physics_time.looping = false;
+        ShouldRun::No
+    }
+}
+
+pub fn reset_physics_time(mut physics_time: ResMut<PhysicsTime>) {
+    physics_time.accumulator = 0.0;
+    physics_time.looping = false;
+    physics_time.tick = 0;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_scale() {
+        let mut physics_time = PhysicsTime::default();
+
+        physics_time.set_scale(0.5);
+        physics_time.advance(PHYSICS_TIMESTEP);
+
+        assert_eq!(physics_time.overstep_percentage(), 0.5);
+
+        physics_time.set_scale(2.0);
+        physics_time.advance(PHYSICS_TIMESTEP);
+
+        assert_eq!(physics_time.overstep_percentage(), 2.5);
+
+        physics_time.advance(10.0);
+
+        assert_eq!(physics_time.overstep_percentage(), MAX_FRAME_STEPS as f32);
+
+        let mut physics_time = PhysicsTime::lockstep();
+
+        physics_time.set_scale(0.0);
+        physics_time.advance(1.0);
+
+        assert_eq!(physics_time.overstep_percentage(), 1.0);
+    }
+}
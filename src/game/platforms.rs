@@ -0,0 +1,313 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+
+use super::{
+    obstacles::{
+        get_obstacle_list, get_tile_bbox, get_tile_list, get_tile_space_bbox, BBox,
+        PlatformObstacle,
+    },
+    physics::{PhysicsTime, PHYSICS_TIMESTEP},
+    player::{PLAYER_HEIGHT_HALF, PLAYER_WIDTH_HALF, SKIN_SIZE},
+    validation::{get_bool, get_field, get_positive_float, join_errors, wrong_type},
+    ObstaclesRes, Player, Position, Velocity, TILE_SIZE,
+};
+
+/// Feet closer than this to the top of a platform are standing on it
+const STANDING_DISTANCE: f32 = 0.5;
+pub const MOVING_PLATFORM_COLOR: Color = Color::rgb(0.3, 0.45, 0.6);
+pub const CRUMBLING_PLATFORM_COLOR: Color = Color::rgb(0.55, 0.4, 0.3);
+
+/// Box the player collides with. Uses `Position` for its center.
+#[derive(Component)]
+pub struct Platform {
+    size: Vec2,
+    /// Can only be landed on, like a one-way tile. Otherwise it blocks on every side like a wall.
+    is_one_way: bool,
+    /// Crumbled platforms don't collide nor show
+    is_active: bool,
+}
+
+impl Platform {
+    pub fn new(size: Vec2, is_one_way: bool) -> Platform {
+        Platform {
+            size,
+            is_one_way,
+            is_active: true,
+        }
+    }
+
+    fn get_bbox(&self, center: Vec2) -> BBox<f32> {
+        let half_size = self.size / 2.0;
+
+        BBox::new(
+            center.x - half_size.x,
+            center.y - half_size.y,
+            center.x + half_size.x,
+            center.y + half_size.y,
+        )
+    }
+
+    /// Player feet on top of the platform, where it was at the start of the step
+    fn is_carrying(
+        &self,
+        position: &Position,
+        player_position: &Vec2,
+        velocity: &Velocity,
+    ) -> bool {
+        let bbox = self.get_bbox(position.previous);
+        let feet_y = player_position.y - PLAYER_HEIGHT_HALF;
+
+        self.is_active
+            && velocity.y <= 0.0
+            && (feet_y - bbox.top).abs() < STANDING_DISTANCE
+            && player_position.x + PLAYER_WIDTH_HALF - SKIN_SIZE > bbox.left
+            && player_position.x - PLAYER_WIDTH_HALF + SKIN_SIZE < bbox.right
+    }
+}
+
+#[derive(Component)]
+pub struct MovingPlatform {
+    /// Centers it goes through in a loop, starting where it's placed
+    path: Vec<Vec2>,
+    /// Pixels per second
+    speed: f32,
+    next: usize,
+}
+
+#[derive(Component)]
+pub struct CrumblingPlatform {
+    /// Seconds from stepping on it until it crumbles
+    delay: f64,
+    /// Seconds until a crumbled platform is back
+    respawn: f64,
+    crumble_at: Option<f64>,
+    respawn_at: Option<f64>,
+}
+
+fn get_platform_size(entity: &EntityInstance) -> Vec2 {
+    Vec2::new(entity.width as f32, entity.height as f32)
+}
+
+/// `center` is where the platform is placed, the `path` points are relative to its grid cell.
+/// Always one-way, a solid one would sweep the player into walls.
+pub fn get_moving_platform_from_entity_instance(
+    entity: &EntityInstance,
+    center: Vec2,
+) -> Result<(Platform, MovingPlatform), String> {
+    let path = get_field(entity, "path").and_then(|field| match &field.value {
+        FieldValue::Points(points) if points.iter().any(Option::is_none) => {
+            Err(String::from("empty point in `path`"))
+        }
+        FieldValue::Points(points) if points.is_empty() => Err(String::from("empty `path`")),
+        FieldValue::Points(points) => Ok(points
+            .iter()
+            .flatten()
+            .map(|point| {
+                // LDtk grid goes down
+                let offset = Vec2::new(
+                    (point.x - entity.grid.x) as f32,
+                    (entity.grid.y - point.y) as f32,
+                );
+
+                center + offset * TILE_SIZE
+            })
+            .collect::<Vec<_>>()),
+        _ => Err(wrong_type(field, "Array<Point>")),
+    });
+
+    let speed = get_positive_float(entity, "speed");
+
+    match (path, speed) {
+        (Ok(path), Ok(speed)) => Ok((
+            Platform::new(get_platform_size(entity), true),
+            MovingPlatform {
+                path: [vec![center], path].concat(),
+                speed,
+                next: 1,
+            },
+        )),
+        (path, speed) => Err(join_errors([path.err(), speed.err()])),
+    }
+}
+
+pub fn get_crumbling_platform_from_entity_instance(
+    entity: &EntityInstance,
+) -> Result<(Platform, CrumblingPlatform), String> {
+    let delay = get_positive_float(entity, "delay");
+    let respawn = get_positive_float(entity, "respawn");
+    let is_one_way = get_bool(entity, "one_way");
+
+    match (delay, respawn, is_one_way) {
+        (Ok(delay), Ok(respawn), Ok(is_one_way)) => Ok((
+            Platform::new(get_platform_size(entity), is_one_way),
+            CrumblingPlatform {
+                delay: delay as f64,
+                respawn: respawn as f64,
+                crumble_at: None,
+                respawn_at: None,
+            },
+        )),
+        (delay, respawn, is_one_way) => {
+            Err(join_errors([delay.err(), respawn.err(), is_one_way.err()]))
+        }
+    }
+}
+
+pub fn move_platforms(mut platforms: Query<(&mut Position, &mut MovingPlatform), Without<Player>>) {
+    for (mut position, mut moving) in platforms.iter_mut() {
+        let target = moving.path[moving.next];
+        let distance = target - position.value;
+        let step = moving.speed * PHYSICS_TIMESTEP as f32;
+
+        position.previous = position.value;
+
+        if distance.length() <= step {
+            position.value = target;
+            moving.next = (moving.next + 1) % moving.path.len();
+        } else {
+            position.value += distance.normalize() * step;
+        }
+    }
+}
+
+pub fn crumble_platforms(
+    physics_time: Res<PhysicsTime>,
+    player_query: Query<(&Position, &Velocity), With<Player>>,
+    mut platforms: Query<(&Position, &mut Platform, &mut CrumblingPlatform), Without<Player>>,
+) {
+    let elapsed = physics_time.elapsed();
+    let player = player_query.get_single().ok();
+
+    for (position, mut platform, mut crumbling) in platforms.iter_mut() {
+        if let Some(respawn_at) = crumbling.respawn_at {
+            if elapsed >= respawn_at {
+                platform.is_active = true;
+                crumbling.respawn_at = None;
+            }
+        } else if let Some(crumble_at) = crumbling.crumble_at {
+            if elapsed >= crumble_at {
+                platform.is_active = false;
+                crumbling.crumble_at = None;
+                crumbling.respawn_at = Some(elapsed + crumbling.respawn);
+            }
+        } else if let Some((player_position, velocity)) = player {
+            if platform.is_carrying(position, &player_position.value, velocity) {
+                crumbling.crumble_at = Some(elapsed + crumbling.delay);
+            }
+        }
+    }
+}
+
+/// Moves the player standing on a platform with it, unless a wall is in the way.
+/// A rising platform waits under a ceiling instead of squashing the player into it.
+pub fn carry_player(
+    obstacles: Res<ObstaclesRes>,
+    mut player_query: Query<(&mut Position, &Velocity), With<Player>>,
+    mut platforms: Query<(&mut Position, &Platform), Without<Player>>,
+) {
+    let (mut player_position, velocity) = player_query.single_mut();
+    let carrier = platforms.iter_mut().find(|(position, platform)| {
+        platform.is_carrying(position, &player_position.value, velocity)
+    });
+
+    if let Some((mut position, _)) = carrier {
+        let delta = position.value - position.previous;
+        let carried_x = player_position.value.x + delta.x;
+        // Slightly narrower, a wall touching the player isn't in the way
+        let carried_bbox = BBox::new(
+            carried_x - PLAYER_WIDTH_HALF + STANDING_DISTANCE,
+            player_position.value.y - PLAYER_HEIGHT_HALF + SKIN_SIZE,
+            carried_x + PLAYER_WIDTH_HALF - STANDING_DISTANCE,
+            player_position.value.y + PLAYER_HEIGHT_HALF - SKIN_SIZE,
+        );
+        let walls = get_obstacle_list(
+            get_tile_list(get_tile_space_bbox(&carried_bbox)),
+            &obstacles.map,
+            true,
+        );
+
+        if walls.is_empty() {
+            player_position.value.x = carried_x;
+        }
+
+        if delta.y != 0.0 {
+            let is_rising = delta.y > 0.0;
+            let y = player_position.value.y;
+            let left = player_position.value.x - PLAYER_WIDTH_HALF + SKIN_SIZE;
+            let right = player_position.value.x + PLAYER_WIDTH_HALF - SKIN_SIZE;
+            // Swept over the move, one-way tiles only block from above
+            let vertical_bbox = if is_rising {
+                let bottom = y + PLAYER_HEIGHT_HALF;
+                BBox::new(left, bottom, right, bottom + delta.y)
+            } else {
+                let top = y - PLAYER_HEIGHT_HALF;
+                BBox::new(left, top + delta.y, right, top)
+            };
+            let vertical_obstacles = get_obstacle_list(
+                get_tile_list(get_tile_space_bbox(&vertical_bbox)),
+                &obstacles.map,
+                is_rising,
+            );
+            let tiles = vertical_obstacles
+                .iter()
+                .map(|obstacle| get_tile_bbox(&obstacle.pos));
+
+            player_position.value.y = if is_rising {
+                tiles
+                    .map(|tile| tile.bottom - PLAYER_HEIGHT_HALF)
+                    .fold(y + delta.y, f32::min)
+                    .max(y)
+            } else {
+                tiles
+                    .map(|tile| tile.top + PLAYER_HEIGHT_HALF)
+                    .fold(y + delta.y, f32::max)
+                    .min(y)
+            };
+
+            if is_rising {
+                position.value.y = position.previous.y + player_position.value.y - y;
+            }
+        }
+    }
+}
+
+/// Platform boxes stretched down over what the platform moved this step
+/// so a rising platform can't go past the feet. One-way ones only keep the top surface.
+pub fn update_platform_obstacles(
+    mut obstacles: ResMut<ObstaclesRes>,
+    platforms: Query<(&Position, &Platform), Without<Player>>,
+) {
+    obstacles.platforms = platforms
+        .iter()
+        .filter(|(_, platform)| platform.is_active)
+        .map(|(position, platform)| {
+            let bbox = platform.get_bbox(position.value);
+            let previous = platform.get_bbox(position.previous);
+            let bottom = if platform.is_one_way {
+                bbox.top.min(previous.top)
+            } else {
+                bbox.bottom.min(previous.bottom)
+            };
+
+            PlatformObstacle {
+                bbox: BBox::new(bbox.left, bottom, bbox.right, bbox.top),
+                is_one_way: platform.is_one_way,
+            }
+        })
+        .collect();
+}
+
+pub fn platform_sprite(
+    physics_time: Res<PhysicsTime>,
+    mut platforms: Query<(&Position, &Platform, &mut Transform, &mut Visibility)>,
+) {
+    for (position, platform, mut transform, mut visibility) in platforms.iter_mut() {
+        let render_position = position
+            .previous
+            .lerp(position.value, physics_time.overstep_percentage());
+
+        transform.translation.x = render_position.x;
+        transform.translation.y = render_position.y;
+        visibility.is_visible = platform.is_active;
+    }
+}
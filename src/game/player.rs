@@ -1,45 +1,175 @@
 use benimator::SpriteSheetAnimation;
 use bevy::prelude::*;
 
-use crate::stats::StatsRes;
+use crate::{
+    loading::GameAssets,
+    stats::{SkinColor, StatsRes},
+    tuning::TuningRes,
+};
 
 use super::{
-    events::{CeilHitEvent, LandingEvent},
-    obstacles::{get_obstacle_list, get_tile_list, get_tile_space_bbox, BBox},
-    Animations, ObstaclesRes, Player, PlayerDirection, Position, Velocity, GRAVITY, PLAYER_HEIGHT,
-    PLAYER_WIDTH, TILE_SIZE,
+    debug::CollisionDebugRes,
+    events::{CeilHitEvent, LandingEvent, WallSlideEvent},
+    obstacles::{
+        get_obstacle_bboxes, get_obstacle_list, get_tile_bbox, get_tile_list, get_tile_space_bbox,
+        BBox,
+    },
+    physics::{PhysicsTime, PHYSICS_TIMESTEP},
+    Animations, ObstaclesRes, Player, PlayerDirection, PlayerText, Position, Velocity, GRAVITY,
+    PLAYER_HEIGHT, PLAYER_WIDTH, TILE_SIZE,
 };
 
-const SKIN_SIZE: f32 = 2.0;
+pub const SKIN_SIZE: f32 = 2.0;
 const PLAYER_SPRITE_HEIGHT: f32 = 48.0;
-const PLAYER_WIDTH_HALF: f32 = PLAYER_WIDTH / 2.0;
+pub const PLAYER_WIDTH_HALF: f32 = PLAYER_WIDTH / 2.0;
 pub const PLAYER_HEIGHT_HALF: f32 = PLAYER_HEIGHT / 2.0;
 
+/// Standing only on one-way tiles or platforms, nothing solid under the feet
+pub fn is_on_one_way_platform(position: &Vec2, obstacles: &ObstaclesRes) -> bool {
+    let feet_y = position.y - PLAYER_HEIGHT_HALF;
+    let left = position.x - PLAYER_WIDTH_HALF + SKIN_SIZE;
+    let right = position.x + PLAYER_WIDTH_HALF - SKIN_SIZE;
+    let ground = get_obstacle_list(
+        get_tile_list(get_tile_space_bbox(&BBox::new(
+            left,
+            feet_y - 1.0,
+            right,
+            feet_y - 1.0,
+        ))),
+        &obstacles.map,
+        false,
+    );
+    let platforms = obstacles
+        .platforms
+        .iter()
+        .filter(|platform| {
+            platform
+                .bbox
+                .overlaps(&BBox::new(left, feet_y, right, feet_y))
+        })
+        .collect::<Vec<_>>();
+
+    (!platforms.is_empty() || !ground.is_empty())
+        && ground.iter().all(|obstacle| obstacle.is_one_way)
+        && platforms.iter().all(|platform| platform.is_one_way)
+}
+
+/// Swaps the skin color of the player sprite sheet for the character one,
+/// the sheet is copied again when the tone changes
 pub fn player_color(
-    _stats: Res<StatsRes>,
-    mut _player_query: Query<&mut TextureAtlasSprite, With<Player>>,
+    stats: Res<StatsRes>,
+    game_assets: Res<GameAssets>,
+    // Not there in headless runs
+    images: Option<ResMut<Assets<Image>>>,
+    atlases: Option<ResMut<Assets<TextureAtlas>>>,
+    mut skin_atlas: Local<Option<(Color, Handle<TextureAtlas>)>>,
+    mut player_query: Query<&mut Handle<TextureAtlas>, With<Player>>,
+) {
+    let (mut images, mut atlases) = match (images, atlases) {
+        (Some(images), Some(atlases)) => (images, atlases),
+        _ => return,
+    };
+    let tone = stats.value.skin_tone;
+
+    if !matches!(&*skin_atlas, Some((skin_tone, _)) if *skin_tone == tone) {
+        let base_atlas = match atlases.get(&game_assets.player_atlas) {
+            Some(atlas) => atlas.clone(),
+            None => return,
+        };
+        let mut image = match images.get(&base_atlas.texture) {
+            Some(image) => image.clone(),
+            None => return,
+        };
+        let from = SkinColor::Light.get_tone().as_rgba_f32();
+        let to = tone.as_rgba_f32();
+        let [from, to] =
+            [from, to].map(|color| color.map(|channel| (channel * 255.0).round() as u8));
+
+        for pixel in image.data.chunks_exact_mut(4) {
+            if pixel[..3] == from[..3] {
+                pixel[..3].copy_from_slice(&to[..3]);
+            }
+        }
+
+        let atlas = TextureAtlas {
+            texture: images.add(image),
+            ..base_atlas
+        };
+
+        *skin_atlas = Some((tone, atlases.add(atlas)));
+    }
+
+    if let Some((_, atlas)) = &*skin_atlas {
+        for mut player_atlas in player_query.iter_mut() {
+            if *player_atlas != *atlas {
+                *player_atlas = atlas.clone();
+            }
+        }
+    }
+}
+
+/// Keeps the `T` text over the player, visible during its episode
+pub fn show_player_text<T: PlayerText>(
+    physics_time: Res<PhysicsTime>,
+    mut text_query: Query<(&mut Visibility, &mut Transform), (With<T>, Without<Player>)>,
+    player_query: Query<(&Player, &Transform), With<Player>>,
 ) {
-    // let mut sprite = player_query.single_mut();
+    let (player, transform) = player_query.single();
+    let (mut text_visibility, mut text_transform) = text_query.single_mut();
+
+    text_visibility.is_visible = T::is_visible(player, physics_time.elapsed());
+    text_transform.translation = transform.translation + Vec3::new(0.0, T::OFFSET_Y, 0.0);
+}
+
+pub fn save_previous_position(mut player_query: Query<&mut Position, With<Player>>) {
+    let mut position = player_query.single_mut();
 
-    // sprite.color = match stats.value.color {
-    //     SkinColor::Light => Color::hex("b8ddf5").unwrap(),
-    //     SkinColor::Medium => Color::hex("3f789d").unwrap(),
-    //     SkinColor::Dark => Color::hex("103954").unwrap(),
-    // };
+    position.previous = position.value;
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn player_movement(
     stats: Res<StatsRes>,
-    time: Res<Time>,
+    tuning: Res<TuningRes>,
+    physics_time: Res<PhysicsTime>,
     obstacles: Res<ObstaclesRes>,
     mut landing_event: EventWriter<LandingEvent>,
     mut ceil_hit_event: EventWriter<CeilHitEvent>,
+    mut wall_slide_event: EventWriter<WallSlideEvent>,
+    mut collision_debug: ResMut<CollisionDebugRes>,
     mut player_query: Query<(&mut Position, &mut Velocity, &mut Player), With<Player>>,
 ) {
     let (mut position, mut velocity, mut player) = player_query.single_mut();
-    let time_delta = time.delta_seconds();
+    let time_delta = PHYSICS_TIMESTEP as f32;
+
+    collision_debug.clear();
+
+    // Falling while pushing against a wall slows the fall down
+    let is_wall_sliding =
+        player.wall_contact.is_some() && velocity.y < 0.0 && player.last_ground_time.is_none();
+
+    if is_wall_sliding {
+        let wall_slide_speed = if player.depressed_until > physics_time.elapsed() {
+            stats.value.wall_slide_speed_depressed
+        } else {
+            stats.value.wall_slide_speed
+        };
+
+        velocity.y = (velocity.y + GRAVITY * tuning.value.wall_slide_gravity * time_delta)
+            .max(-wall_slide_speed);
 
-    velocity.y += GRAVITY * time_delta;
+        if !player.is_wall_sliding {
+            wall_slide_event.send(WallSlideEvent {
+                position: position.value,
+                side: player.wall_contact.unwrap(),
+            });
+        }
+    } else {
+        velocity.y += GRAVITY * time_delta;
+    }
+
+    player.is_wall_sliding = is_wall_sliding;
+    player.wall_contact = None;
 
     let is_moving_right = velocity.x > 0.0;
     let is_moving_up = velocity.y > 0.0;
@@ -65,20 +195,46 @@ pub fn player_movement(
             true,
         );
 
+        collision_debug.add_sweep(&horizontal_bbox, &horizontal_obstacles);
+
+        // One-way platforms don't block sideways
+        let solid_platforms = obstacles
+            .platforms
+            .iter()
+            .filter(|platform| !platform.is_one_way)
+            .map(|platform| platform.bbox.clone())
+            .collect::<Vec<_>>();
+        let horizontal_bboxes =
+            get_obstacle_bboxes(&horizontal_bbox, &horizontal_obstacles, &solid_platforms);
+
         let nearest_obstacle_x = if is_moving_right {
-            let tile_x = horizontal_obstacles.iter().map(|o| o.pos.0).min();
-            tile_x.map(|x| x as f32 * TILE_SIZE - PLAYER_WIDTH_HALF)
+            let obstacle_x = horizontal_bboxes.iter().map(|b| b.left).reduce(f32::min);
+            obstacle_x.map(|x| x - PLAYER_WIDTH_HALF)
         } else {
-            let tile_x = horizontal_obstacles.iter().map(|o| o.pos.0).max();
-            tile_x.map(|x| x as f32 * TILE_SIZE + TILE_SIZE + PLAYER_WIDTH_HALF)
+            let obstacle_x = horizontal_bboxes.iter().map(|b| b.right).reduce(f32::max);
+            obstacle_x.map(|x| x + PLAYER_WIDTH_HALF)
         };
 
         if let Some(nearest_obstacle_x) = nearest_obstacle_x {
+            let is_blocked = if is_moving_right {
+                pos_x >= nearest_obstacle_x
+            } else {
+                pos_x <= nearest_obstacle_x
+            };
+
             position.value.x = if is_moving_right {
                 pos_x.min(nearest_obstacle_x)
             } else {
                 pos_x.max(nearest_obstacle_x)
             };
+
+            if is_blocked {
+                player.wall_contact = Some(if is_moving_right {
+                    PlayerDirection::Right
+                } else {
+                    PlayerDirection::Left
+                });
+            }
         } else {
             position.value.x = pos_x;
         }
@@ -99,18 +255,41 @@ pub fn player_movement(
             BBox::new(left, bottom, right, top)
         };
 
-        let vertical_obstacles = get_obstacle_list(
+        let ignore_one_way = is_moving_up && stats.value.can_skip_one_way_platforms;
+        // Only the one-way surface dropped through, a platform right below it still holds
+        let is_dropping = player.drop_until > physics_time.elapsed();
+        let drop_y = player.drop_y;
+        let is_dropped_through = |top: f32| is_dropping && top > drop_y - TILE_SIZE;
+
+        let mut vertical_obstacles = get_obstacle_list(
             get_tile_list(get_tile_space_bbox(&vertical_bbox)),
             &obstacles.map,
-            is_moving_up && stats.value.can_skip_one_way_platforms,
+            ignore_one_way,
         );
 
+        vertical_obstacles.retain(|obstacle| {
+            !obstacle.is_one_way || !is_dropped_through(get_tile_bbox(&obstacle.pos).top)
+        });
+
+        collision_debug.add_sweep(&vertical_bbox, &vertical_obstacles);
+
+        // One-way platforms can only be landed on
+        let platforms = obstacles
+            .platforms
+            .iter()
+            .filter(|platform| {
+                !platform.is_one_way || !is_moving_up && !is_dropped_through(platform.bbox.top)
+            })
+            .map(|platform| platform.bbox.clone())
+            .collect::<Vec<_>>();
+        let vertical_bboxes = get_obstacle_bboxes(&vertical_bbox, &vertical_obstacles, &platforms);
+
         let nearest_obstacle_y = if is_moving_up {
-            let tile_y = vertical_obstacles.iter().map(|o| o.pos.1).min();
-            tile_y.map(|y| y as f32 * TILE_SIZE - PLAYER_HEIGHT_HALF)
+            let obstacle_y = vertical_bboxes.iter().map(|b| b.bottom).reduce(f32::min);
+            obstacle_y.map(|y| y - PLAYER_HEIGHT_HALF)
         } else {
-            let tile_y = vertical_obstacles.iter().map(|o| o.pos.1).max();
-            tile_y.map(|y| y as f32 * TILE_SIZE + TILE_SIZE + PLAYER_HEIGHT_HALF)
+            let obstacle_y = vertical_bboxes.iter().map(|b| b.top).reduce(f32::max);
+            obstacle_y.map(|y| y + PLAYER_HEIGHT_HALF)
         };
 
         if let Some(nearest_obstacle_y) = nearest_obstacle_y {
@@ -128,7 +307,8 @@ pub fn player_movement(
                     }
 
                     velocity.y = 0.0;
-                    player.last_ground_time = Some(time.seconds_since_startup());
+                    player.last_ground_time = Some(physics_time.elapsed());
+                    player.jump_force = None;
                 } else {
                     velocity.y = -velocity.y * 0.1;
                     ceil_hit_event.send(CeilHitEvent {
@@ -147,7 +327,8 @@ pub fn player_movement(
 pub fn player_animation(
     animations: Res<Animations>,
     stats: Res<StatsRes>,
-    time: Res<Time>,
+    tuning: Res<TuningRes>,
+    physics_time: Res<PhysicsTime>,
     mut player_query: Query<
         (
             &mut Transform,
@@ -166,23 +347,29 @@ pub fn player_animation(
     let is_grounded = velocity.y == 0.0;
     let is_running = is_grounded && velocity.x != 0.0;
     let is_jumping = !is_grounded;
-    let is_buffered_jump_valid = player.is_buffered_jump_valid(time.seconds_since_startup());
+    let is_buffered_jump_valid =
+        player.is_buffered_jump_valid(physics_time.elapsed(), tuning.value.jump_buffer_time);
+
+    let render_position = position
+        .previous
+        .lerp(position.value, physics_time.overstep_percentage());
 
-    sprite_transform.translation.x = position.value.x;
+    sprite_transform.translation.x = render_position.x;
     sprite_transform.translation.y =
-        position.value.y + (PLAYER_SPRITE_HEIGHT - PLAYER_HEIGHT) / 2.0;
+        render_position.y + (PLAYER_SPRITE_HEIGHT - PLAYER_HEIGHT) / 2.0;
     sprite_transform.translation.z = 10.0;
 
     sprite.flip_x = player.direction == PlayerDirection::Left;
 
-    if is_jumping {
-        // Map velocity.y to animation frame
-        let force_range = stats.value.jump_force * 2.0;
+    if player.is_wall_sliding {
+        *animation = animations.wall_slide.clone();
+    } else if is_jumping {
+        // Map velocity.y to animation frame, short jumps go through all the frames too
+        let jump_force = player.jump_force.unwrap_or(stats.value.jump_force);
+        let force_range = jump_force * 2.0;
         let total_frames = animations.jump.len() as f32;
-        let velocity = velocity
-            .y
-            .clamp(-stats.value.jump_force, stats.value.jump_force);
-        let frame = (force_range - (velocity + stats.value.jump_force)) / force_range;
+        let velocity = velocity.y.clamp(-jump_force, jump_force);
+        let frame = (force_range - (velocity + jump_force)) / force_range;
         let frame = (frame * total_frames).min(total_frames - 1.0) as usize;
 
         *animation = animations.jump[frame].clone();
@@ -194,3 +381,403 @@ pub fn player_animation(
         *animation = animations.idle.clone();
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game::{
+        events::{DirectionChangeEvent, JumpEvent, WallJumpEvent},
+        input::{handle_input, PlayerInput},
+        obstacles::{Obstacle, Point},
+        psychosis::Hallucination,
+    };
+    use crate::{
+        game::{campaign::CampaignRes, physics::step_physics},
+        GameState,
+    };
+    use crate::{
+        stats::{Intelligence, MentalHealth, SkinColor, Stats, Strength, Wealth},
+        tuning::Tuning,
+    };
+    use bevy::ecs::{event::Events, schedule::ShouldRun};
+
+    /// Strong unless told otherwise, the other attributes don't change the movement
+    fn get_stats(strength: Strength) -> Stats {
+        Stats::from_config(
+            &Tuning::default(),
+            SkinColor::Light,
+            MentalHealth::Healthy,
+            true,
+            Intelligence::Smart,
+            true,
+            strength,
+            Wealth::Rich,
+        )
+    }
+
+    /// Tiles and whether they are one-way
+    fn get_obstacles(tiles: impl IntoIterator<Item = (Point<i32>, bool)>) -> ObstaclesRes {
+        ObstaclesRes {
+            map: tiles
+                .into_iter()
+                .map(|(pos, is_one_way)| (pos.clone(), Obstacle { pos, is_one_way }))
+                .collect(),
+            platforms: vec![],
+        }
+    }
+
+    /// What `handle_input` and `player_movement` use, with a grounded player at the origin
+    fn get_world(stats: Stats, obstacles: ObstaclesRes, input: PlayerInput) -> (World, Entity) {
+        let mut world = World::new();
+        world.insert_resource(StatsRes::new(stats));
+        world.insert_resource(TuningRes::default());
+        world.insert_resource(PhysicsTime::default());
+        world.insert_resource(obstacles);
+        world.insert_resource(input);
+        world.insert_resource(Events::<JumpEvent>::default());
+        world.insert_resource(Events::<DirectionChangeEvent>::default());
+        world.insert_resource(Events::<LandingEvent>::default());
+        world.insert_resource(Events::<CeilHitEvent>::default());
+        world.insert_resource(Events::<WallSlideEvent>::default());
+        world.insert_resource(Events::<WallJumpEvent>::default());
+        world.insert_resource(CollisionDebugRes::default());
+
+        let player = world
+            .spawn()
+            .insert(Player {
+                direction: PlayerDirection::Right,
+                depressed_until: 0.0,
+                psychotic_until: 0.0,
+                hallucination: Hallucination::FakePlatforms,
+                blink_until: 0.0,
+                invulnerable_until: 0.0,
+                drop_until: 0.0,
+                drop_y: 0.0,
+                lifes: 1,
+                checkpoint: None,
+                family_rescues: 0,
+                bounce_force: None,
+                last_ground_time: Some(0.0),
+                buffer_jump_time: None,
+                jump_force: None,
+                jump_min_force: None,
+                wall_contact: None,
+                is_wall_sliding: false,
+            })
+            .insert(Position::new(Vec2::ZERO))
+            .insert(Velocity { x: 0.0, y: 0.0 })
+            .id();
+
+        (world, player)
+    }
+
+    fn get_jump_apex(strength: Strength, is_depressed: bool, jump_held: bool) -> f32 {
+        let (mut world, player) = get_world(
+            get_stats(strength),
+            get_obstacles([]),
+            PlayerInput {
+                jump: true,
+                jump_held,
+                ..Default::default()
+            },
+        );
+
+        if is_depressed {
+            world.get_mut::<Player>(player).unwrap().depressed_until = 1.0;
+        }
+
+        let mut stage = SystemStage::single_threaded()
+            .with_system(handle_input.before(player_movement))
+            .with_system(player_movement);
+        let mut apex: f32 = 0.0;
+
+        loop {
+            stage.run(&mut world);
+            apex = apex.max(world.get::<Position>(player).unwrap().value.y);
+
+            if world.get::<Velocity>(player).unwrap().y <= 0.0 {
+                return apex;
+            }
+        }
+    }
+
+    #[test]
+    fn test_jump_apex() {
+        // Weak clears a 4 tiles hall + 1 ground tile, Strong clears one more
+        let weak = get_jump_apex(Strength::Weak, false, true);
+        assert!(weak > 5.0 * TILE_SIZE && weak < 6.0 * TILE_SIZE, "{weak}");
+
+        let strong = get_jump_apex(Strength::Strong, false, true);
+        assert!(
+            strong > 6.0 * TILE_SIZE && strong < 7.0 * TILE_SIZE,
+            "{strong}"
+        );
+
+        // Depressed barely clears 5 tiles, whatever the strength
+        for strength in [Strength::Weak, Strength::Strong] {
+            let depressed = get_jump_apex(strength, true, true);
+            assert!(
+                depressed > 5.0 * TILE_SIZE && depressed < weak,
+                "{depressed}"
+            );
+        }
+    }
+
+    /// Frame length the stage is run with, instead of `Time`
+    struct FrameDelta(f64);
+
+    /// Highest point and when the jump landed
+    #[derive(Default)]
+    struct JumpTrace {
+        apex: f32,
+        landing: Option<f64>,
+    }
+
+    fn frame_timestep(
+        frame_delta: Res<FrameDelta>,
+        app_state: Res<State<GameState>>,
+        campaign: Res<CampaignRes>,
+        mut physics_time: ResMut<PhysicsTime>,
+    ) -> ShouldRun {
+        step_physics(frame_delta.0, &app_state, &campaign, &mut physics_time)
+    }
+
+    /// Jump only pressed on the first step, then trace it
+    fn trace_jump(
+        physics_time: Res<PhysicsTime>,
+        mut input: ResMut<PlayerInput>,
+        mut trace: ResMut<JumpTrace>,
+        player_query: Query<(&Position, &Player)>,
+    ) {
+        let (position, player) = player_query.single();
+
+        input.jump = false;
+        trace.apex = trace.apex.max(position.value.y);
+
+        if trace.landing.is_none() && trace.apex > 0.0 && player.last_ground_time.is_some() {
+            trace.landing = Some(physics_time.elapsed());
+        }
+    }
+
+    /// Full jump from a solid floor through the physics run criteria, at `fps` frames per second
+    fn trace_jump_at(fps: f64) -> (f32, Option<f64>) {
+        let (mut world, player) = get_world(
+            get_stats(Strength::Strong),
+            get_obstacles((-2..=2).map(|x| (Point(x, -1), false))),
+            PlayerInput {
+                jump: true,
+                jump_held: true,
+                ..Default::default()
+            },
+        );
+        world.insert_resource(State::new(GameState::Game));
+        world.insert_resource(CampaignRes {
+            is_loading: false,
+            ..Default::default()
+        });
+        world.insert_resource(FrameDelta(1.0 / fps));
+        world.insert_resource(JumpTrace::default());
+        world
+            .get_mut::<Position>(player)
+            .unwrap()
+            .teleport(Vec2::new(8.0, PLAYER_HEIGHT_HALF));
+
+        let mut stage = SystemStage::single_threaded()
+            .with_run_criteria(frame_timestep)
+            .with_system(handle_input.before(player_movement))
+            .with_system(player_movement)
+            .with_system(trace_jump.after(player_movement));
+
+        // Two seconds of frames
+        for _ in 0..(2.0 * fps) as usize {
+            stage.run(&mut world);
+        }
+
+        let trace = world.resource::<JumpTrace>();
+
+        (trace.apex, trace.landing)
+    }
+
+    #[test]
+    fn test_frame_rate_independence() {
+        let (apex, landing) = trace_jump_at(60.0);
+
+        assert!(apex > 5.0 * TILE_SIZE, "{apex}");
+        assert!(landing.is_some());
+
+        for fps in [30.0, 144.0] {
+            assert_eq!(trace_jump_at(fps), (apex, landing), "{fps} fps");
+        }
+    }
+
+    #[test]
+    fn test_jump_cut_short() {
+        // Releasing Jump right away still clears a 1 tile step
+        let weak = get_jump_apex(Strength::Weak, false, false);
+        assert!(weak > TILE_SIZE && weak < 3.0 * TILE_SIZE, "{weak}");
+
+        let strong = get_jump_apex(Strength::Strong, false, false);
+        assert!(strong > weak && strong < 3.0 * TILE_SIZE, "{strong}");
+
+        let depressed = get_jump_apex(Strength::Strong, true, false);
+        assert!(depressed > TILE_SIZE && depressed < weak, "{depressed}");
+    }
+
+    /// Lowest point after Down+Jump on a floor of one-way or solid tiles,
+    /// with another one-way floor 2 tiles below
+    fn get_drop_lowest_point(is_one_way: bool, can_drop_through_platforms: bool) -> f32 {
+        let mut stats = get_stats(Strength::Strong);
+        stats.can_drop_through_platforms = can_drop_through_platforms;
+
+        let (mut world, player) = get_world(
+            stats,
+            get_obstacles(
+                (-2..=2).flat_map(|x| [(Point(x, -1), is_one_way), (Point(x, -3), true)]),
+            ),
+            PlayerInput {
+                jump: true,
+                jump_held: true,
+                down: true,
+                ..Default::default()
+            },
+        );
+        world
+            .get_mut::<Position>(player)
+            .unwrap()
+            .teleport(Vec2::new(8.0, PLAYER_HEIGHT_HALF));
+
+        let mut stage = SystemStage::single_threaded()
+            .with_system(handle_input.before(player_movement))
+            .with_system(player_movement);
+        let mut lowest_point = PLAYER_HEIGHT_HALF;
+
+        for _ in 0..30 {
+            stage.run(&mut world);
+            lowest_point = lowest_point.min(world.get::<Position>(player).unwrap().value.y);
+        }
+
+        lowest_point
+    }
+
+    /// Horizontal velocity after a step holding Right
+    fn get_velocity_x(hallucination: Hallucination) -> f32 {
+        let (mut world, player) = get_world(
+            get_stats(Strength::Strong),
+            get_obstacles([]),
+            PlayerInput {
+                right: true,
+                ..Default::default()
+            },
+        );
+        let mut player_state = world.get_mut::<Player>(player).unwrap();
+        player_state.psychotic_until = 1.0;
+        player_state.hallucination = hallucination;
+
+        SystemStage::single_threaded()
+            .with_system(handle_input)
+            .run(&mut world);
+
+        world.get::<Velocity>(player).unwrap().x
+    }
+
+    #[test]
+    fn test_flipped_controls() {
+        assert!(get_velocity_x(Hallucination::FlippedControls) < 0.0);
+        assert!(get_velocity_x(Hallucination::DistortedView) > 0.0);
+    }
+
+    #[test]
+    fn test_drop_through() {
+        // Lands on the floor below, it isn't dropped through too
+        assert_eq!(
+            get_drop_lowest_point(true, true),
+            -2.0 * TILE_SIZE + PLAYER_HEIGHT_HALF
+        );
+        // Solid floor or not allowed, it's a regular jump
+        assert_eq!(get_drop_lowest_point(false, true), PLAYER_HEIGHT_HALF);
+        assert_eq!(get_drop_lowest_point(true, false), PLAYER_HEIGHT_HALF);
+    }
+
+    /// Falls along a wall on the right while pushing against it, then jumps off it
+    fn slide_and_jump(strength: Strength) -> (Velocity, Velocity) {
+        let (mut world, player) = get_world(
+            get_stats(strength),
+            get_obstacles((-20..20).map(|y| (Point(1, y), false))),
+            PlayerInput {
+                right: true,
+                ..Default::default()
+            },
+        );
+        world.get_mut::<Player>(player).unwrap().last_ground_time = None;
+
+        let mut stage = SystemStage::single_threaded()
+            .with_system(handle_input.before(player_movement))
+            .with_system(player_movement);
+
+        for _ in 0..60 {
+            stage.run(&mut world);
+        }
+
+        let velocity = world.get::<Velocity>(player).unwrap();
+        let sliding = Velocity {
+            x: velocity.x,
+            y: velocity.y,
+        };
+
+        world.resource_mut::<PlayerInput>().jump = true;
+        stage.run(&mut world);
+
+        let velocity = world.get::<Velocity>(player).unwrap();
+        let jumping = Velocity {
+            x: velocity.x,
+            y: velocity.y,
+        };
+
+        (sliding, jumping)
+    }
+
+    #[test]
+    fn test_wall_slide_and_jump() {
+        let tuning = Tuning::default();
+        let (weak_sliding, weak_jumping) = slide_and_jump(Strength::Weak);
+        let (strong_sliding, strong_jumping) = slide_and_jump(Strength::Strong);
+
+        // Falls at the max slide speed, the strong one grips better
+        assert_eq!(weak_sliding.y, -tuning.wall_slide_speed_weak);
+        assert_eq!(strong_sliding.y, -tuning.wall_slide_speed_strong);
+
+        // Kicked up and away from the wall, the strong one higher and farther
+        assert!(weak_jumping.y > 0.0 && weak_jumping.x < 0.0);
+        assert!(strong_jumping.y > weak_jumping.y);
+        assert!(strong_jumping.x < weak_jumping.x);
+    }
+
+    #[test]
+    fn test_collision_debug() {
+        let floor = Point(0, -1);
+        let (mut world, player) = get_world(
+            get_stats(Strength::Strong),
+            get_obstacles([(floor.clone(), false)]),
+            PlayerInput::default(),
+        );
+
+        // Standing on the floor tile
+        world
+            .get_mut::<Position>(player)
+            .unwrap()
+            .teleport(Vec2::new(0.0, PLAYER_HEIGHT_HALF));
+        world.get_mut::<Velocity>(player).unwrap().x = 50.0;
+
+        let mut stage = SystemStage::single_threaded().with_system(player_movement);
+
+        stage.run(&mut world);
+        assert!(world.resource::<CollisionDebugRes>().sweeps.is_empty());
+
+        world.resource_mut::<CollisionDebugRes>().enabled = true;
+        stage.run(&mut world);
+
+        let collision_debug = world.resource::<CollisionDebugRes>();
+        assert_eq!(collision_debug.sweeps.len(), 2);
+        assert_eq!(collision_debug.hits, [floor]);
+    }
+}
@@ -0,0 +1,184 @@
+use bevy::prelude::*;
+use rand::{
+    distributions::{Distribution, Standard},
+    Rng,
+};
+
+use crate::{
+    stats::StatsRes,
+    tuning::{Tuning, TuningRes},
+};
+
+use super::{
+    camera::GameCamera,
+    events::PsychosisEvent,
+    obstacles::{get_obstacle_list, get_tile_list, get_tile_space_bbox, BBox},
+    physics::PhysicsTime,
+    platforms::MOVING_PLATFORM_COLOR,
+    GameStateEntity, LevelEntity, ObstaclesRes, Player, Position, TILE_SIZE,
+};
+
+/// Fake platforms shown around the player, they can't be stood on
+const HALLUCINATED_PLATFORMS: usize = 4;
+const HALLUCINATED_PLATFORM_WIDTH: f32 = 3.0 * TILE_SIZE;
+
+/// What the player suffers during a psychotic episode
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "dev", derive(bevy_inspector_egui::Inspectable))]
+pub enum Hallucination {
+    FakePlatforms,
+    /// Left moves right and right moves left
+    FlippedControls,
+    /// The camera sways and zooms
+    DistortedView,
+}
+
+impl Distribution<Hallucination> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Hallucination {
+        match rng.gen_range(0..=2) {
+            0 => Hallucination::FakePlatforms,
+            1 => Hallucination::FlippedControls,
+            _ => Hallucination::DistortedView,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct HallucinatedPlatform {
+    until: f64,
+}
+
+pub fn trigger_psychosis(
+    mut stats: ResMut<StatsRes>,
+    tuning: Res<TuningRes>,
+    physics_time: Res<PhysicsTime>,
+    mut players: Query<&mut Player>,
+    mut psychosis_event: EventWriter<PsychosisEvent>,
+) {
+    let mut player = players.single_mut();
+
+    let can_get_psychotic = stats.value.is_psychotic
+        && (player.psychotic_until + tuning.value.psychosis_cooldown) < physics_time.elapsed();
+
+    if can_get_psychotic && stats.rng.gen_range(0.0..1.0) < stats.value.psychosis_chance {
+        start_psychosis(
+            &mut stats,
+            &tuning.value,
+            physics_time.elapsed(),
+            &mut player,
+            &mut psychosis_event,
+        );
+    }
+}
+
+/// Starts a psychotic episode of random duration and hallucination
+pub fn start_psychosis(
+    stats: &mut StatsRes,
+    tuning: &Tuning,
+    elapsed: f64,
+    player: &mut Player,
+    psychosis_event: &mut EventWriter<PsychosisEvent>,
+) {
+    let duration = stats
+        .rng
+        .gen_range(tuning.min_psychosis_duration..tuning.max_psychosis_duration);
+    let hallucination = stats.rng.gen();
+
+    player.psychotic_until = elapsed + duration;
+    player.hallucination = hallucination;
+    psychosis_event.send(PsychosisEvent {
+        duration,
+        hallucination,
+    });
+}
+
+/// Places the fake platforms on free spots around the player
+pub fn add_hallucinated_platforms(
+    mut commands: Commands,
+    mut stats: ResMut<StatsRes>,
+    physics_time: Res<PhysicsTime>,
+    obstacles: Res<ObstaclesRes>,
+    player_query: Query<&Position, With<Player>>,
+    mut psychosis_event: EventReader<PsychosisEvent>,
+) {
+    let position = player_query.single();
+    let size = Vec2::new(HALLUCINATED_PLATFORM_WIDTH, TILE_SIZE);
+
+    for event in psychosis_event.iter() {
+        if event.hallucination != Hallucination::FakePlatforms {
+            continue;
+        }
+
+        for _ in 0..HALLUCINATED_PLATFORMS {
+            let offset = Vec2::new(
+                stats.rng.gen_range(-8..=8) as f32,
+                stats.rng.gen_range(-2..=6) as f32,
+            ) * TILE_SIZE;
+            let center = ((position.value + offset) / TILE_SIZE).floor() * TILE_SIZE + size / 2.0;
+            let bottom_left = center - size / 2.0;
+            let top_right = center + size / 2.0 - Vec2::ONE;
+            let tiles = get_obstacle_list(
+                get_tile_list(get_tile_space_bbox(&BBox::new(
+                    bottom_left.x,
+                    bottom_left.y,
+                    top_right.x,
+                    top_right.y,
+                ))),
+                &obstacles.map,
+                false,
+            );
+
+            if !tiles.is_empty() {
+                continue;
+            }
+
+            commands
+                .spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: MOVING_PLATFORM_COLOR,
+                        custom_size: Some(size),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_translation(center.extend(5.0)),
+                    ..Default::default()
+                })
+                .insert(HallucinatedPlatform {
+                    until: physics_time.elapsed() + event.duration,
+                })
+                .insert(LevelEntity)
+                .insert(GameStateEntity);
+        }
+    }
+}
+
+pub fn remove_hallucinated_platforms(
+    mut commands: Commands,
+    physics_time: Res<PhysicsTime>,
+    platforms: Query<(Entity, &HallucinatedPlatform)>,
+) {
+    for (entity, platform) in platforms.iter() {
+        if platform.until <= physics_time.elapsed() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+pub fn distort_view(
+    physics_time: Res<PhysicsTime>,
+    player_query: Query<&Player>,
+    mut cameras: Query<&mut Transform, With<GameCamera>>,
+) {
+    let player = player_query.single();
+    let mut camera_transform = cameras.single_mut();
+    let elapsed = physics_time.elapsed();
+
+    if player.get_hallucination(elapsed) == Some(Hallucination::DistortedView) {
+        let time = elapsed as f32;
+
+        camera_transform.rotation = Quat::from_rotation_z((time * 1.7).sin() * 0.12);
+        camera_transform.scale = Vec3::splat(1.0 + (time * 2.3).sin() * 0.15);
+    } else {
+        camera_transform.rotation = Quat::IDENTITY;
+        camera_transform.scale = Vec3::ONE;
+    }
+}
@@ -0,0 +1,418 @@
+use bevy::prelude::*;
+use rand::Rng;
+use std::{
+    fmt::Debug,
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    stats::{Intelligence, MentalHealth, SkinColor, Stats, StatsRes, Strength, Wealth},
+    tuning::{Tuning, TuningRes},
+};
+
+use super::input::PlayerInput;
+
+const REPLAY_HEADER: &str = "fortuna-replay 5";
+const REPLAYS_DIR: &str = "replays";
+
+/// Everything needed to reproduce a run: the tuning, the character, the RNG seed
+/// and the input of every physics step
+#[derive(Debug, PartialEq)]
+pub struct Replay {
+    pub tuning: Tuning,
+    pub stats: Stats,
+    pub run_seed: u64,
+    pub inputs: Vec<PlayerInput>,
+}
+
+impl Replay {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Replay, String> {
+        let content = fs::read_to_string(path).map_err(|err| err.to_string())?;
+
+        Replay::parse(&content)
+    }
+
+    /// Text format, inputs are run-length encoded as `<ticks>*<key bits>`
+    ///
+    /// ```text
+    /// fortuna-replay 5
+    /// tuning (run_top_speed_strong:160.0,run_top_speed_weak:140.0, ...)
+    /// seed 1234
+    /// stats Light Healthy true Smart true Weak Rich 0.3 0.2
+    /// run_seed 42
+    /// inputs 120*0 4*2 1*14 3*10
+    /// ```
+    pub fn serialize(&self) -> String {
+        let stats = &self.stats;
+        let mut lines = vec![REPLAY_HEADER.to_string()];
+
+        lines.push(format!(
+            "tuning {}",
+            ron::to_string(&self.tuning).expect("Tuning serializes")
+        ));
+
+        if let Some(seed) = stats.seed {
+            lines.push(format!("seed {seed}"));
+        }
+
+        lines.push(format!(
+            "stats {:?} {:?} {} {:?} {} {:?} {:?} {} {}",
+            stats.color,
+            stats.mental_health,
+            stats.has_supportive_family,
+            stats.intelligence,
+            stats.is_male,
+            stats.strength,
+            stats.wealth,
+            stats.depre_chance,
+            stats.psychosis_chance,
+        ));
+        lines.push(format!("run_seed {}", self.run_seed));
+
+        let mut runs: Vec<(usize, u8)> = vec![];
+
+        for input in self.inputs.iter() {
+            let bits = input_to_bits(input);
+
+            match runs.last_mut() {
+                Some((count, last_bits)) if *last_bits == bits => *count += 1,
+                _ => runs.push((1, bits)),
+            }
+        }
+
+        let mut inputs = vec![String::from("inputs")];
+        inputs.extend(runs.iter().map(|(count, bits)| format!("{count}*{bits}")));
+
+        lines.push(inputs.join(" "));
+        lines.join("\n") + "\n"
+    }
+
+    pub fn parse(content: &str) -> Result<Replay, String> {
+        let mut lines = content.lines();
+
+        if lines.next() != Some(REPLAY_HEADER) {
+            return Err(String::from("not a replay file"));
+        }
+
+        let mut tuning = None;
+        let mut seed = None;
+        let mut stats = None;
+        let mut run_seed = None;
+        let mut inputs = vec![];
+
+        for line in lines {
+            let mut words = line.split_whitespace();
+
+            match words.next() {
+                Some("tuning") => {
+                    let value = line.trim_start().trim_start_matches("tuning");
+
+                    tuning = Some(Tuning::parse(value).map_err(|err| format!("`tuning`: {err}"))?);
+                }
+                Some("seed") => seed = Some(parse_number(words.next())?),
+                Some("stats") => {
+                    let words = words.collect::<Vec<_>>();
+
+                    if words.len() != 9 {
+                        return Err(format!("expected 9 stats, got {}", words.len()));
+                    }
+
+                    stats = Some(words);
+                }
+                Some("run_seed") => run_seed = Some(parse_number(words.next())?),
+                Some("inputs") => {
+                    for run in words {
+                        let (count, bits) = run
+                            .split_once('*')
+                            .ok_or_else(|| format!("invalid input run `{run}`"))?;
+                        let count: usize = parse_number(Some(count))?;
+                        let input = input_from_bits(parse_number(Some(bits))?);
+
+                        inputs.extend(vec![input; count]);
+                    }
+                }
+                Some(key) => return Err(format!("unknown key `{key}`")),
+                None => {}
+            }
+        }
+
+        let tuning = tuning.ok_or("missing `tuning`")?;
+        let words = stats.ok_or("missing `stats`")?;
+        let mut stats = Stats::from_config(
+            &tuning,
+            parse_variant(
+                words[0],
+                [SkinColor::Light, SkinColor::Medium, SkinColor::Dark],
+            )?,
+            parse_variant(
+                words[1],
+                [
+                    MentalHealth::Healthy,
+                    MentalHealth::Depressive,
+                    MentalHealth::Psychotic,
+                ],
+            )?,
+            parse_number(Some(words[2]))?,
+            parse_variant(words[3], [Intelligence::Dumb, Intelligence::Smart])?,
+            parse_number(Some(words[4]))?,
+            parse_variant(words[5], [Strength::Weak, Strength::Strong])?,
+            parse_variant(words[6], [Wealth::Poor, Wealth::MiddleClass, Wealth::Rich])?,
+        );
+
+        stats.depre_chance = parse_number(Some(words[7]))?;
+        stats.psychosis_chance = parse_number(Some(words[8]))?;
+        stats.seed = seed;
+
+        Ok(Replay {
+            tuning,
+            stats,
+            run_seed: run_seed.ok_or("missing `run_seed`")?,
+            inputs,
+        })
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(word: Option<&str>) -> Result<T, String> {
+    let word = word.ok_or("missing value")?;
+
+    word.parse().map_err(|_| format!("invalid value `{word}`"))
+}
+
+fn parse_variant<T: Debug, const N: usize>(word: &str, variants: [T; N]) -> Result<T, String> {
+    variants
+        .into_iter()
+        .find(|variant| format!("{variant:?}") == word)
+        .ok_or_else(|| format!("invalid value `{word}`"))
+}
+
+fn input_to_bits(input: &PlayerInput) -> u8 {
+    input.left as u8
+        | (input.right as u8) << 1
+        | (input.jump as u8) << 2
+        | (input.jump_held as u8) << 3
+        | (input.down as u8) << 4
+}
+
+fn input_from_bits(bits: u8) -> PlayerInput {
+    PlayerInput {
+        left: bits & 1 != 0,
+        right: bits & 2 != 0,
+        jump: bits & 4 != 0,
+        jump_held: bits & 8 != 0,
+        down: bits & 16 != 0,
+    }
+}
+
+#[derive(Default)]
+pub struct ReplayRes {
+    /// Loaded with `--replay`, fed to the physics steps instead of the keyboard
+    pub playback: Option<Replay>,
+    /// Run being recorded
+    current: Option<Replay>,
+}
+
+impl ReplayRes {
+    pub fn new(playback: Option<Replay>) -> ReplayRes {
+        ReplayRes {
+            playback,
+            current: None,
+        }
+    }
+
+    /// Sets the input of the next physics step of the playback, for bots that decide as they play
+    pub fn set_next_input(&mut self, input: PlayerInput) {
+        let tick = self
+            .current
+            .as_ref()
+            .map(|current| current.inputs.len())
+            .unwrap_or_default();
+
+        if let Some(playback) = self.playback.as_mut() {
+            playback.inputs.resize(tick, PlayerInput::default());
+            playback.inputs.push(input);
+        }
+    }
+}
+
+/// A replay plays back with the tuning it was recorded with, not the current one
+pub fn apply_replay_stats(
+    replay: Res<ReplayRes>,
+    mut tuning: ResMut<TuningRes>,
+    mut stats: ResMut<StatsRes>,
+) {
+    if let Some(playback) = &replay.playback {
+        if tuning.value != playback.tuning {
+            println!(
+                "The replay was recorded with a different tuning, playing it back with that one"
+            );
+            tuning.value = playback.tuning.clone();
+        }
+
+        *stats = StatsRes::new(playback.stats.clone());
+    }
+}
+
+/// Runs when the character menu closes, the character can't change after this.
+/// Resuming from the pause menu keeps the run going.
+pub fn start_replay(
+    tuning: Res<TuningRes>,
+    mut replay: ResMut<ReplayRes>,
+    mut stats: ResMut<StatsRes>,
+) {
+    if replay.current.is_some() {
+        return;
+    }
+
+    let run_seed = match &replay.playback {
+        Some(playback) => playback.run_seed,
+        None => stats.rng.gen(),
+    };
+
+    stats.reseed_rng(run_seed);
+
+    replay.current = Some(Replay {
+        tuning: tuning.value.clone(),
+        stats: stats.value.clone(),
+        run_seed,
+        inputs: vec![],
+    });
+}
+
+pub fn replay_input(mut replay: ResMut<ReplayRes>, mut input: ResMut<PlayerInput>) {
+    let replay = &mut *replay;
+
+    if let Some(current) = replay.current.as_mut() {
+        if let Some(playback) = &replay.playback {
+            *input = playback
+                .inputs
+                .get(current.inputs.len())
+                .copied()
+                .unwrap_or_default();
+        }
+
+        current.inputs.push(*input);
+    }
+}
+
+/// `<timestamp>.replay` in `dir`, numbered if a replay already has that name
+fn get_replay_path(dir: &Path, timestamp: u128) -> PathBuf {
+    (0..)
+        .map(|count| match count {
+            0 => dir.join(format!("{timestamp}.replay")),
+            count => dir.join(format!("{timestamp}-{count}.replay")),
+        })
+        .find(|path| !path.exists())
+        .expect("There's always a free name")
+}
+
+pub fn save_replay(mut replay: ResMut<ReplayRes>) {
+    let current = match replay.current.take() {
+        Some(current) => current,
+        None => return,
+    };
+
+    if replay.playback.is_some() || current.inputs.is_empty() {
+        return;
+    }
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis())
+        .unwrap_or_default();
+    let path = get_replay_path(Path::new(REPLAYS_DIR), timestamp);
+
+    match fs::create_dir_all(REPLAYS_DIR).and_then(|_| fs::write(&path, current.serialize())) {
+        Ok(_) => println!("Replay saved: {}", path.display()),
+        Err(err) => println!("Can't save replay: {err}"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_serialize_parse() {
+        let mut inputs = vec![PlayerInput::default(); 120];
+        inputs.extend(vec![
+            PlayerInput {
+                left: false,
+                right: true,
+                jump: false,
+                jump_held: false,
+                down: false,
+            };
+            4
+        ]);
+        inputs.push(PlayerInput {
+            left: false,
+            right: true,
+            jump: true,
+            jump_held: true,
+            down: false,
+        });
+        inputs.extend(vec![
+            PlayerInput {
+                left: false,
+                right: true,
+                jump: false,
+                jump_held: true,
+                down: false,
+            };
+            3
+        ]);
+        inputs.push(PlayerInput {
+            left: false,
+            right: false,
+            jump: true,
+            jump_held: true,
+            down: true,
+        });
+
+        let tuning = Tuning::parse(
+            &ron::to_string(&Tuning {
+                goo_speed: 40.5,
+                drop_through_allow: Some(String::from("wealth>=middle")),
+                ..Default::default()
+            })
+            .unwrap(),
+        )
+        .unwrap();
+
+        let replay = Replay {
+            stats: Stats::from_seed(1234, &tuning),
+            tuning,
+            run_seed: 42,
+            inputs,
+        };
+        let content = replay.serialize();
+
+        assert!(content.ends_with("run_seed 42\ninputs 120*0 4*2 1*14 3*10 1*28\n"));
+        assert_eq!(Replay::parse(&content), Ok(replay));
+
+        assert!(Replay::parse("fortuna-replay 5\nrun_seed 42\n").is_err());
+        // Missing the tuning
+        assert!(Replay::parse(&content.replace(content.lines().nth(1).unwrap(), "")).is_err());
+        assert!(Replay::parse("something else").is_err());
+    }
+
+    #[test]
+    fn test_get_replay_path() {
+        let dir = std::env::temp_dir().join(format!("fortuna-replays-{}", std::process::id()));
+
+        fs::create_dir_all(&dir).unwrap();
+
+        let first = get_replay_path(&dir, 1000);
+        fs::write(&first, "").unwrap();
+        let second = get_replay_path(&dir, 1000);
+        fs::write(&second, "").unwrap();
+
+        assert_eq!(first, dir.join("1000.replay"));
+        assert_eq!(second, dir.join("1000-1.replay"));
+        assert_eq!(get_replay_path(&dir, 1000), dir.join("1000-2.replay"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,367 @@
+use crate::stats::Stats;
+
+/// Stats a rule can check, enums compare by the order of their values
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Stat {
+    Color,
+    MentalHealth,
+    Intelligence,
+    Strength,
+    Wealth,
+    Lifes,
+    IsMale,
+    HasSupportiveFamily,
+    IsDepressive,
+    IsPsychotic,
+}
+
+impl Stat {
+    fn from_name(name: &str) -> Option<Stat> {
+        match name {
+            "color" => Some(Stat::Color),
+            "mental_health" => Some(Stat::MentalHealth),
+            "intelligence" => Some(Stat::Intelligence),
+            "strength" => Some(Stat::Strength),
+            "wealth" => Some(Stat::Wealth),
+            "lifes" => Some(Stat::Lifes),
+            "is_male" => Some(Stat::IsMale),
+            "has_supportive_family" => Some(Stat::HasSupportiveFamily),
+            "is_depressive" => Some(Stat::IsDepressive),
+            "is_psychotic" => Some(Stat::IsPsychotic),
+            _ => None,
+        }
+    }
+
+    /// Value names, in order
+    fn values(&self) -> &'static [&'static str] {
+        match self {
+            Stat::Color => &["light", "medium", "dark"],
+            Stat::MentalHealth => &["healthy", "depressive", "psychotic"],
+            Stat::Intelligence => &["dumb", "smart"],
+            Stat::Strength => &["weak", "strong"],
+            Stat::Wealth => &["poor", "middle", "rich"],
+            _ => &[],
+        }
+    }
+
+    fn is_flag(&self) -> bool {
+        matches!(
+            self,
+            Stat::IsMale | Stat::HasSupportiveFamily | Stat::IsDepressive | Stat::IsPsychotic
+        )
+    }
+
+    fn get(&self, stats: &Stats) -> i32 {
+        match self {
+            Stat::Color => stats.color as i32,
+            Stat::MentalHealth => stats.mental_health as i32,
+            Stat::Intelligence => stats.intelligence as i32,
+            Stat::Strength => stats.strength as i32,
+            Stat::Wealth => stats.wealth as i32,
+            Stat::Lifes => stats.lifes,
+            Stat::IsMale => stats.is_male as i32,
+            Stat::HasSupportiveFamily => stats.has_supportive_family as i32,
+            Stat::IsDepressive => stats.is_depressive as i32,
+            Stat::IsPsychotic => stats.is_psychotic as i32,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Op {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+/// Condition over `Stats` written in LDtk fields, e.g. `wealth>=middle && !is_male`
+#[derive(Clone, Debug, PartialEq)]
+pub enum Rule {
+    Flag(Stat),
+    Compare(Stat, Op, i32),
+    Not(Box<Rule>),
+    And(Box<Rule>, Box<Rule>),
+    Or(Box<Rule>, Box<Rule>),
+}
+
+impl Rule {
+    pub fn parse(source: &str) -> Result<Rule, String> {
+        let mut parser = Parser {
+            tokens: tokenize(source)?,
+            pos: 0,
+        };
+        let rule = parser.parse_or()?;
+
+        match parser.tokens.get(parser.pos) {
+            None => Ok(rule),
+            Some(token) => Err(format!("unexpected `{}`", token.text())),
+        }
+    }
+
+    pub fn eval(&self, stats: &Stats) -> bool {
+        match self {
+            Rule::Flag(stat) => stat.get(stats) != 0,
+            Rule::Compare(stat, op, value) => {
+                let stat = stat.get(stats);
+
+                match op {
+                    Op::Eq => stat == *value,
+                    Op::Ne => stat != *value,
+                    Op::Gt => stat > *value,
+                    Op::Ge => stat >= *value,
+                    Op::Lt => stat < *value,
+                    Op::Le => stat <= *value,
+                }
+            }
+            Rule::Not(rule) => !rule.eval(stats),
+            Rule::And(a, b) => a.eval(stats) && b.eval(stats),
+            Rule::Or(a, b) => a.eval(stats) || b.eval(stats),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Word(String),
+    Op(Op),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+impl Token {
+    fn text(&self) -> String {
+        match self {
+            Token::Word(word) => word.clone(),
+            Token::Op(op) => String::from(match op {
+                Op::Eq => "==",
+                Op::Ne => "!=",
+                Op::Gt => ">",
+                Op::Ge => ">=",
+                Op::Lt => "<",
+                Op::Le => "<=",
+            }),
+            Token::And => String::from("&&"),
+            Token::Or => String::from("||"),
+            Token::Not => String::from("!"),
+            Token::Open => String::from("("),
+            Token::Close => String::from(")"),
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = source.chars().peekable();
+
+    while let Some(char) = chars.next() {
+        let next_is_eq = chars.peek() == Some(&'=');
+
+        let token = match char {
+            ' ' | '\t' => continue,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '&' if chars.next_if_eq(&'&').is_some() => Token::And,
+            '|' if chars.next_if_eq(&'|').is_some() => Token::Or,
+            '=' if next_is_eq => Token::Op(Op::Eq),
+            '!' if next_is_eq => Token::Op(Op::Ne),
+            '>' if next_is_eq => Token::Op(Op::Ge),
+            '<' if next_is_eq => Token::Op(Op::Le),
+            '!' => Token::Not,
+            '>' => Token::Op(Op::Gt),
+            '<' => Token::Op(Op::Lt),
+            char if char.is_ascii_alphanumeric() || char == '_' => {
+                let mut word = String::from(char);
+
+                while let Some(char) =
+                    chars.next_if(|char| char.is_ascii_alphanumeric() || *char == '_')
+                {
+                    word.push(char);
+                }
+
+                Token::Word(word)
+            }
+            char => return Err(format!("unexpected `{char}`")),
+        };
+
+        // Skip the `=` of two chars operators
+        if matches!(token, Token::Op(Op::Eq | Op::Ne | Op::Ge | Op::Le)) {
+            chars.next();
+        }
+
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+/// Recursive descent, `!` binds tighter than `&&`, which binds tighter than `||`
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<&Token> {
+        self.pos += 1;
+        self.tokens.get(self.pos - 1)
+    }
+
+    fn next_if(&mut self, token: Token) -> bool {
+        if self.tokens.get(self.pos) == Some(&token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Rule, String> {
+        let mut rule = self.parse_and()?;
+
+        while self.next_if(Token::Or) {
+            rule = Rule::Or(Box::new(rule), Box::new(self.parse_and()?));
+        }
+
+        Ok(rule)
+    }
+
+    fn parse_and(&mut self) -> Result<Rule, String> {
+        let mut rule = self.parse_unary()?;
+
+        while self.next_if(Token::And) {
+            rule = Rule::And(Box::new(rule), Box::new(self.parse_unary()?));
+        }
+
+        Ok(rule)
+    }
+
+    fn parse_unary(&mut self) -> Result<Rule, String> {
+        if self.next_if(Token::Not) {
+            return Ok(Rule::Not(Box::new(self.parse_unary()?)));
+        }
+
+        if self.next_if(Token::Open) {
+            let rule = self.parse_or()?;
+
+            return if self.next_if(Token::Close) {
+                Ok(rule)
+            } else {
+                Err(String::from("missing `)`"))
+            };
+        }
+
+        let name = match self.next() {
+            Some(Token::Word(name)) => name.clone(),
+            Some(token) => return Err(format!("expected a stat, got `{}`", token.text())),
+            None => return Err(String::from("expected a stat, got the end of the rule")),
+        };
+        let stat = Stat::from_name(&name).ok_or_else(|| format!("unknown stat `{name}`"))?;
+
+        let op = match self.tokens.get(self.pos) {
+            Some(Token::Op(op)) => *op,
+            _ if stat.is_flag() => return Ok(Rule::Flag(stat)),
+            _ => return Err(format!("`{name}` needs a comparison, e.g. `{name}==...`")),
+        };
+
+        self.pos += 1;
+
+        let value = match self.next() {
+            Some(Token::Word(value)) => value.clone(),
+            _ => return Err(format!("missing value to compare `{name}` with")),
+        };
+
+        let value = match stat {
+            Stat::Lifes => value
+                .parse()
+                .map_err(|_| format!("`lifes` is a number, got `{value}`"))?,
+            _ if stat.is_flag() => match value.as_str() {
+                "true" => 1,
+                "false" => 0,
+                _ => return Err(format!("`{name}` is true or false, got `{value}`")),
+            },
+            _ => stat
+                .values()
+                .iter()
+                .position(|name| *name == value)
+                .ok_or_else(|| {
+                    format!(
+                        "unknown value `{value}` for `{name}`, expected one of: {}",
+                        stat.values().join(", ")
+                    )
+                })? as i32,
+        };
+
+        Ok(Rule::Compare(stat, op, value))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::stats::{Intelligence, MentalHealth, SkinColor, Strength, Wealth};
+    use crate::tuning::Tuning;
+
+    fn get_stats(wealth: Wealth, is_male: bool) -> Stats {
+        Stats::from_config(
+            &Tuning::default(),
+            SkinColor::Dark,
+            MentalHealth::Healthy,
+            false,
+            Intelligence::Smart,
+            is_male,
+            Strength::Weak,
+            wealth,
+        )
+    }
+
+    #[test]
+    fn test_eval() {
+        let rule = Rule::parse("wealth>=middle && !is_male").unwrap();
+
+        assert!(rule.eval(&get_stats(Wealth::Rich, false)));
+        assert!(rule.eval(&get_stats(Wealth::MiddleClass, false)));
+        assert!(!rule.eval(&get_stats(Wealth::Poor, false)));
+        assert!(!rule.eval(&get_stats(Wealth::Rich, true)));
+
+        let rule = Rule::parse("color == light || (strength!=weak || lifes > 1)").unwrap();
+
+        assert!(!rule.eval(&get_stats(Wealth::Poor, true)));
+        assert!(rule.eval(&get_stats(Wealth::MiddleClass, true)));
+
+        let rule = Rule::parse("!!is_male && is_male==true").unwrap();
+
+        assert!(rule.eval(&get_stats(Wealth::Poor, true)));
+        assert!(!rule.eval(&get_stats(Wealth::Poor, false)));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let errors = [
+            ("", "expected a stat, got the end of the rule"),
+            ("rich", "unknown stat `rich`"),
+            ("wealth", "`wealth` needs a comparison, e.g. `wealth==...`"),
+            (
+                "wealth==upper",
+                "unknown value `upper` for `wealth`, expected one of: poor, middle, rich",
+            ),
+            (
+                "wealth==rich &&",
+                "expected a stat, got the end of the rule",
+            ),
+            ("(is_male", "missing `)`"),
+            ("is_male is_male", "unexpected `is_male`"),
+            ("is_male & is_male", "unexpected `&`"),
+            ("lifes>=many", "`lifes` is a number, got `many`"),
+        ];
+
+        for (source, error) in errors {
+            assert_eq!(Rule::parse(source), Err(String::from(error)), "{source}");
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use bevy::prelude::*;
+
+use super::{
+    events::{
+        BounceEvent, CeilHitEvent, DepressionEvent, FamilyRescueEvent, GooHitEvent, JumpEvent,
+        LandingEvent,
+    },
+    physics::{PhysicsTime, PHYSICS_TIMESTEP},
+    Player, Position, TILE_SIZE,
+};
+
+/// What happened during a run, shown when it ends
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RunStats {
+    /// Seconds of play, without menus and pauses
+    pub time: f64,
+    /// Highest player position, in tiles
+    pub highest_point: f32,
+    pub jumps: u32,
+    pub landings: u32,
+    pub ceil_hits: u32,
+    pub goo_hits: u32,
+    /// Times the family caught the player before the goo
+    pub family_rescues: u32,
+    pub bounces: u32,
+    pub depressions: u32,
+    /// Seconds spent depressed
+    pub depressed_time: f64,
+}
+
+impl RunStats {
+    pub fn get_summary(&self) -> String {
+        let mut lines = vec![
+            format!("Time: {:.1}s", self.time),
+            format!("Highest point: {:.0} tiles", self.highest_point),
+            format!("Jumps: {}", self.jumps),
+            format!("Landings: {}", self.landings),
+            format!("Ceiling hits: {}", self.ceil_hits),
+            format!("Lives lost to the goo: {}", self.goo_hits),
+            format!("Turned away by bouncers: {}", self.bounces),
+        ];
+
+        if self.family_rescues > 0 {
+            lines.push(format!(
+                "Caught by your family before the goo: {}",
+                self.family_rescues
+            ));
+        }
+
+        if self.depressions > 0 {
+            lines.push(format!(
+                "Depressed {} times, for {:.1}s",
+                self.depressions, self.depressed_time
+            ));
+        }
+
+        lines.join("\n")
+    }
+}
+
+#[derive(Default)]
+pub struct RunStatsRes {
+    pub value: RunStats,
+}
+
+pub fn reset_run_stats(mut run_stats: ResMut<RunStatsRes>) {
+    run_stats.value = RunStats::default();
+}
+
+pub fn track_run(
+    physics_time: Res<PhysicsTime>,
+    mut run_stats: ResMut<RunStatsRes>,
+    mut jump_event: EventReader<JumpEvent>,
+    mut landing_event: EventReader<LandingEvent>,
+    mut ceil_hit_event: EventReader<CeilHitEvent>,
+    mut goo_hit_event: EventReader<GooHitEvent>,
+    mut family_rescue_event: EventReader<FamilyRescueEvent>,
+    mut bounce_event: EventReader<BounceEvent>,
+    mut depression_event: EventReader<DepressionEvent>,
+    player_query: Query<(&Player, &Position)>,
+) {
+    let run_stats = &mut run_stats.value;
+
+    run_stats.time = physics_time.elapsed();
+    run_stats.jumps += jump_event.iter().count() as u32;
+    run_stats.landings += landing_event.iter().count() as u32;
+    run_stats.ceil_hits += ceil_hit_event.iter().count() as u32;
+    run_stats.goo_hits += goo_hit_event.iter().count() as u32;
+    run_stats.family_rescues += family_rescue_event.iter().count() as u32;
+    run_stats.bounces += bounce_event.iter().count() as u32;
+    run_stats.depressions += depression_event.iter().count() as u32;
+
+    if let Ok((player, position)) = player_query.get_single() {
+        run_stats.highest_point = run_stats.highest_point.max(position.value.y / TILE_SIZE);
+
+        if player.depressed_until > physics_time.elapsed() {
+            run_stats.depressed_time += PHYSICS_TIMESTEP;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_get_summary() {
+        let mut run_stats = RunStats {
+            time: 62.34,
+            highest_point: 87.4,
+            jumps: 40,
+            landings: 38,
+            ceil_hits: 3,
+            goo_hits: 2,
+            family_rescues: 0,
+            bounces: 1,
+            depressions: 0,
+            depressed_time: 0.0,
+        };
+
+        assert_eq!(
+            run_stats.get_summary(),
+            "Time: 62.3s\nHighest point: 87 tiles\nJumps: 40\nLandings: 38\nCeiling hits: 3\n\
+             Lives lost to the goo: 2\nTurned away by bouncers: 1"
+        );
+
+        run_stats.family_rescues = 1;
+        run_stats.depressions = 2;
+        run_stats.depressed_time = 7.5;
+
+        assert!(run_stats
+            .get_summary()
+            .ends_with("\nCaught by your family before the goo: 1\nDepressed 2 times, for 7.5s"));
+    }
+}
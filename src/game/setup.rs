@@ -1,21 +1,41 @@
 use benimator::{Play, SpriteSheetAnimation};
-use bevy::prelude::*;
+use bevy::{ecs::schedule::ShouldRun, prelude::*};
 use bevy_ecs_ldtk::{EntityInstance, GridCoords, LdtkWorldBundle, LevelEvent};
 use std::time::Duration;
 
 use crate::{
     loading::{GameAssets, UIAssets},
     stats::{StatsRes, Wealth},
+    tuning::TuningRes,
     GameState,
 };
 
 use super::{
-    bouncer, camera, goal,
+    bouncer, camera,
+    checkpoint::{self, Checkpoint},
+    goal, goo,
     obstacles::{Obstacle, Point},
-    Animations, DepressedText, GameStateEntity, LifesText, ObstaclesRes, OneWayPlatform, Player,
-    PlayerDirection, PlayerPositionsRes, Position, Velocity, Wall,
+    platforms,
+    psychosis::Hallucination,
+    validation, Animations, DepressedText, GameStateEntity, LevelEntity, LifesText, ObstaclesRes,
+    OneWayPlatform, Player, PlayerDirection, PlayerPositionsRes, Position, PsychoticText, Velocity,
+    Wall,
 };
 
+/// `SystemSet::on_in_stack_update` stops running after resuming the state (Bevy 0.7)
+pub fn is_game_in_stack(app_state: Res<State<GameState>>) -> ShouldRun {
+    if *app_state.current() == GameState::Game || app_state.inactives().contains(&GameState::Game) {
+        ShouldRun::Yes
+    } else {
+        ShouldRun::No
+    }
+}
+
+/// The character may have been created before the tuning asset loaded
+pub fn apply_tuning(tuning: Res<TuningRes>, mut stats: ResMut<StatsRes>) {
+    stats.value.apply_tuning(&tuning.value);
+}
+
 pub fn show_character_menu(mut app_state: ResMut<State<GameState>>) {
     app_state.push(GameState::CharacterMenu).unwrap();
 }
@@ -70,6 +90,26 @@ pub fn setup_game(
         .insert(GameStateEntity)
         .insert(DepressedText);
 
+    // Add psychotic message
+    commands
+        .spawn_bundle(Text2dBundle {
+            text: Text::with_section(
+                "They're all watching me...",
+                TextStyle {
+                    font: ui_assets.font.clone(),
+                    font_size: 10.0,
+                    color: Color::BLACK,
+                },
+                TextAlignment {
+                    vertical: VerticalAlign::Center,
+                    horizontal: HorizontalAlign::Center,
+                },
+            ),
+            ..Default::default()
+        })
+        .insert(GameStateEntity)
+        .insert(PsychoticText);
+
     commands
         .spawn_bundle(TextBundle {
             style: Style {
@@ -169,6 +209,12 @@ pub fn setup_animations(
     animations.vfx_landing_dust =
         animation_sheets.add(SpriteSheetAnimation::from_range(19..=36, frame_duration).once());
 
+    // Hand on the wall while the body slides down, slower than the other animations
+    animations.wall_slide = animation_sheets.add(SpriteSheetAnimation::from_range(
+        35..=36,
+        Duration::from_millis(120),
+    ));
+
     animations.jump = vec![
         animation_sheets.add(SpriteSheetAnimation::from_range(25..=25, frame_duration)),
         animation_sheets.add(SpriteSheetAnimation::from_range(26..=26, frame_duration)),
@@ -193,17 +239,60 @@ fn get_entities<'a>(
         .collect::<Vec<_>>()
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn setup_entities(
     stats: Res<StatsRes>,
     game_assets: Res<GameAssets>,
     animations: Res<Animations>,
     mut player_positions: ResMut<PlayerPositionsRes>,
+    mut goo_settings: ResMut<goo::GooSettingsRes>,
     mut commands: Commands,
     entities: Query<(&Transform, &EntityInstance), Added<EntityInstance>>,
+    level_entities: Query<Entity, With<LevelEntity>>,
+    mut player_query: Query<(&mut Player, &mut Position, &mut Velocity)>,
+    mut level_problems: ResMut<validation::LevelProblemsRes>,
+    mut app_state: ResMut<State<GameState>>,
 ) {
+    let instances = entities
+        .iter()
+        .map(|(_, instance)| instance)
+        .collect::<Vec<_>>();
+
+    if !instances.is_empty() {
+        let problems = validation::validate_entities(&instances);
+
+        if !problems.is_empty() {
+            println!(
+                "The level can't be played:\n{}",
+                problems
+                    .iter()
+                    .map(|problem| format!("  - {problem}"))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            );
+
+            level_problems.value = problems;
+            app_state.overwrite_replace(GameState::MainMenu).unwrap();
+            return;
+        }
+    }
+
     let player_entities = get_entities(&entities, "Player");
 
     if !player_entities.is_empty() {
+        // A new level spawned, remove the entities of the previous one
+        for entity in level_entities.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+
+        // The tuning ones if the level has no settings
+        goo_settings.value = get_entities(&entities, "GooSettings")
+            .first()
+            .map(|(_, entity)| {
+                goo::get_goo_settings_from_entity_instance(entity)
+                    .expect("Goo settings are validated")
+            });
+
         // Prepare Player Positions Resource
         player_positions.value = player_entities
             .iter()
@@ -226,29 +315,45 @@ pub fn setup_entities(
             Wealth::Poor => 2,
         };
 
-        let &transform = player_positions.value.get(pos).unwrap();
-
-        commands
-            .spawn_bundle(SpriteSheetBundle {
-                texture_atlas: game_assets.player_atlas.clone(),
-                ..Default::default()
-            })
-            .insert(animations.idle.clone())
-            .insert(Play)
-            .insert(Player {
-                direction: PlayerDirection::Right,
-                depressed_until: 0.0,
-                blink_until: 0.0,
-                last_ground_time: None,
-                lifes: stats.value.lifes,
-                bounce_force: None,
-                buffer_jump_time: None,
-            })
-            .insert(Position {
-                value: transform.translation.truncate(),
-            })
-            .insert(Velocity { x: 0.0, y: 0.0 })
-            .insert(GameStateEntity);
+        let transform = player_positions.value[pos];
+
+        // Keep the player (and its lifes) between levels
+        if let Ok((mut player, mut position, mut velocity)) = player_query.get_single_mut() {
+            player.checkpoint = None;
+            position.teleport(transform.translation.truncate());
+            *velocity = Velocity { x: 0.0, y: 0.0 };
+        } else {
+            commands
+                .spawn_bundle(SpriteSheetBundle {
+                    texture_atlas: game_assets.player_atlas.clone(),
+                    ..Default::default()
+                })
+                .insert(animations.idle.clone())
+                .insert(Play)
+                .insert(Player {
+                    direction: PlayerDirection::Right,
+                    depressed_until: 0.0,
+                    psychotic_until: 0.0,
+                    hallucination: Hallucination::FakePlatforms,
+                    drop_until: 0.0,
+                    drop_y: 0.0,
+                    blink_until: 0.0,
+                    invulnerable_until: 0.0,
+                    last_ground_time: None,
+                    lifes: stats.value.lifes,
+                    checkpoint: None,
+                    family_rescues: stats.value.family_rescues,
+                    bounce_force: None,
+                    buffer_jump_time: None,
+                    jump_force: None,
+                    jump_min_force: None,
+                    wall_contact: None,
+                    is_wall_sliding: false,
+                })
+                .insert(Position::new(transform.translation.truncate()))
+                .insert(Velocity { x: 0.0, y: 0.0 })
+                .insert(GameStateEntity);
+        }
     }
 
     let goal_entities = get_entities(&entities, "Goal");
@@ -273,6 +378,7 @@ pub fn setup_entities(
                 ..Default::default()
             })
             .insert(goal::Goal)
+            .insert(LevelEntity)
             .insert(GameStateEntity);
     }
 
@@ -280,6 +386,8 @@ pub fn setup_entities(
 
     for bouncer_entity in bouncer_entities {
         let (bouncer_transform, bouncer_entity) = bouncer_entity;
+        let bouncer = bouncer::get_bouncer_from_entity_instance(bouncer_entity)
+            .expect("Bouncers are validated");
 
         commands
             .spawn_bundle(SpriteBundle {
@@ -297,7 +405,84 @@ pub fn setup_entities(
                 },
                 ..Default::default()
             })
-            .insert(bouncer::get_bouncer_from_entity_instance(bouncer_entity))
+            .insert(bouncer)
+            .insert(LevelEntity)
+            .insert(GameStateEntity);
+    }
+
+    for (checkpoint_transform, checkpoint_entity) in get_entities(&entities, "Checkpoint") {
+        let center = checkpoint_transform.translation.truncate();
+        let size = get_entity_size(checkpoint_entity);
+
+        commands
+            .spawn_bundle(SpriteBundle {
+                visibility: Visibility {
+                    is_visible: stats.value.can_use_checkpoints,
+                },
+                sprite: Sprite {
+                    color: checkpoint::CHECKPOINT_COLOR,
+                    custom_size: Some(size),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(center.extend(4.0)),
+                ..Default::default()
+            })
+            .insert(Checkpoint::new(center, size))
+            .insert(LevelEntity)
             .insert(GameStateEntity);
     }
+
+    for (platform_transform, platform_entity) in get_entities(&entities, "MovingPlatform") {
+        let center = platform_transform.translation.truncate();
+        let (platform, moving_platform) =
+            platforms::get_moving_platform_from_entity_instance(platform_entity, center)
+                .expect("Moving platforms are validated");
+
+        commands
+            .spawn_bundle(get_platform_sprite(
+                platform_entity,
+                center,
+                platforms::MOVING_PLATFORM_COLOR,
+            ))
+            .insert(platform)
+            .insert(moving_platform)
+            .insert(Position::new(center))
+            .insert(LevelEntity)
+            .insert(GameStateEntity);
+    }
+
+    for (platform_transform, platform_entity) in get_entities(&entities, "CrumblingPlatform") {
+        let center = platform_transform.translation.truncate();
+        let (platform, crumbling_platform) =
+            platforms::get_crumbling_platform_from_entity_instance(platform_entity)
+                .expect("Crumbling platforms are validated");
+
+        commands
+            .spawn_bundle(get_platform_sprite(
+                platform_entity,
+                center,
+                platforms::CRUMBLING_PLATFORM_COLOR,
+            ))
+            .insert(platform)
+            .insert(crumbling_platform)
+            .insert(Position::new(center))
+            .insert(LevelEntity)
+            .insert(GameStateEntity);
+    }
+}
+
+fn get_entity_size(entity: &EntityInstance) -> Vec2 {
+    Vec2::new(entity.width as f32, entity.height as f32)
+}
+
+fn get_platform_sprite(entity: &EntityInstance, center: Vec2, color: Color) -> SpriteBundle {
+    SpriteBundle {
+        sprite: Sprite {
+            color,
+            custom_size: Some(get_entity_size(entity)),
+            ..Default::default()
+        },
+        transform: Transform::from_translation(center.extend(5.0)),
+        ..Default::default()
+    }
 }
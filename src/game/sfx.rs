@@ -1,6 +1,6 @@
 use bevy::prelude::*;
 
-use super::events::{CeilHitEvent, JumpEvent, LandingEvent};
+use super::events::{CeilHitEvent, FamilyRescueEvent, JumpEvent, LandingEvent, WallJumpEvent};
 use crate::loading::GameAssets;
 
 pub fn play_jump_sound(
@@ -13,6 +13,16 @@ pub fn play_jump_sound(
     }
 }
 
+pub fn play_wall_jump_sound(
+    audio: Res<Audio>,
+    game_assets: Res<GameAssets>,
+    mut wall_jump_event: EventReader<WallJumpEvent>,
+) {
+    for _ in wall_jump_event.iter() {
+        audio.play(game_assets.jump_sound.clone());
+    }
+}
+
 pub fn play_landing_sound(mut landing_event: EventReader<LandingEvent>) {
     for _ in landing_event.iter() {
         println!("Play landing sound");
@@ -24,3 +34,9 @@ pub fn play_ceil_hit_sound(mut ceil_hit_event: EventReader<CeilHitEvent>) {
         println!("Play ceil-hit sound");
     }
 }
+
+pub fn play_family_rescue_sound(mut family_rescue_event: EventReader<FamilyRescueEvent>) {
+    for _ in family_rescue_event.iter() {
+        println!("Play family rescue sound");
+    }
+}
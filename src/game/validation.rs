@@ -0,0 +1,208 @@
+use bevy_ecs_ldtk::{
+    ldtk::{FieldInstance, FieldValue},
+    EntityInstance,
+};
+
+use bevy::prelude::Vec2;
+
+use super::{bouncer, goo, platforms};
+
+const PLAYER_SPAWN_POINTS: usize = 3; // One per `Wealth`
+
+/// Why the last level couldn't be played, shown in the main menu
+#[derive(Default)]
+pub struct LevelProblemsRes {
+    pub value: Vec<String>,
+}
+
+pub fn get_field<'a>(
+    entity: &'a EntityInstance,
+    identifier: &str,
+) -> Result<&'a FieldInstance, String> {
+    entity
+        .field_instances
+        .iter()
+        .find(|field| field.identifier == identifier)
+        .ok_or_else(|| format!("missing field `{identifier}`"))
+}
+
+pub fn wrong_type(field: &FieldInstance, expected: &str) -> String {
+    format!(
+        "field `{}` should be a {expected}, got {}",
+        field.identifier, field.field_instance_type
+    )
+}
+
+pub fn get_bool(entity: &EntityInstance, identifier: &str) -> Result<bool, String> {
+    get_field(entity, identifier).and_then(|field| match field.value {
+        FieldValue::Bool(value) => Ok(value),
+        _ => Err(wrong_type(field, "Bool")),
+    })
+}
+
+pub fn get_float(entity: &EntityInstance, identifier: &str) -> Result<f32, String> {
+    get_field(entity, identifier).and_then(|field| match field.value {
+        FieldValue::Float(Some(value)) => Ok(value),
+        FieldValue::Float(None) => Err(format!("empty `{identifier}`")),
+        _ => Err(wrong_type(field, "Float")),
+    })
+}
+
+pub fn get_positive_float(entity: &EntityInstance, identifier: &str) -> Result<f32, String> {
+    get_float(entity, identifier).and_then(|value| match value {
+        value if value > 0.0 => Ok(value),
+        value => Err(format!(
+            "field `{identifier}` should be positive, got {value}"
+        )),
+    })
+}
+
+/// All the field errors of an entity in one message, so they can be fixed at once
+pub fn join_errors<const N: usize>(errors: [Option<String>; N]) -> String {
+    errors.into_iter().flatten().collect::<Vec<_>>().join(", ")
+}
+
+fn describe(entity: &EntityInstance) -> String {
+    format!(
+        "{} {} at [{},{}]",
+        entity.identifier, entity.iid, entity.grid.x, entity.grid.y
+    )
+}
+
+/// Everything wrong with the entities of a level, empty if it can be played
+pub fn validate_entities(entities: &[&EntityInstance]) -> Vec<String> {
+    let mut problems = vec![];
+    let get_entities = |identifier: &str| {
+        entities
+            .iter()
+            .filter(|entity| entity.identifier == identifier)
+            .collect::<Vec<_>>()
+    };
+
+    let players = get_entities("Player");
+
+    if players.len() < PLAYER_SPAWN_POINTS {
+        problems.push(format!(
+            "found {} Player spawn points, {PLAYER_SPAWN_POINTS} are needed (one per wealth)",
+            players.len()
+        ));
+    }
+
+    let goals = get_entities("Goal");
+
+    match goals.len() {
+        0 => problems.push(String::from("there is no Goal")),
+        1 => {}
+        _ => problems.push(format!(
+            "there must be one Goal, found {}: {}",
+            goals.len(),
+            goals
+                .iter()
+                .map(|goal| describe(goal))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+
+    let goo_settings = get_entities("GooSettings");
+
+    if goo_settings.len() > 1 {
+        problems.push(format!(
+            "there can be one GooSettings at most, found {}",
+            goo_settings.len()
+        ));
+    }
+
+    for entity in goo_settings {
+        if let Err(err) = goo::get_goo_settings_from_entity_instance(entity) {
+            problems.push(format!("{}: {err}", describe(entity)));
+        }
+    }
+
+    for entity in get_entities("Bouncer") {
+        if let Err(err) = bouncer::get_bouncer_from_entity_instance(entity) {
+            problems.push(format!("{}: {err}", describe(entity)));
+        }
+    }
+
+    for entity in get_entities("MovingPlatform") {
+        if let Err(err) = platforms::get_moving_platform_from_entity_instance(entity, Vec2::ZERO) {
+            problems.push(format!("{}: {err}", describe(entity)));
+        }
+    }
+
+    for entity in get_entities("CrumblingPlatform") {
+        if let Err(err) = platforms::get_crumbling_platform_from_entity_instance(entity) {
+            problems.push(format!("{}: {err}", describe(entity)));
+        }
+    }
+
+    problems
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::headless::load_project;
+
+    #[test]
+    fn test_validate_entities() {
+        let project = load_project();
+
+        for level in project.levels.iter() {
+            let entities = level
+                .layer_instances
+                .iter()
+                .flatten()
+                .flat_map(|layer| layer.entity_instances.iter())
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                validate_entities(&entities),
+                Vec::<String>::new(),
+                "{}",
+                level.identifier
+            );
+        }
+
+        let mut entities = project.levels[0]
+            .layer_instances
+            .iter()
+            .flatten()
+            .flat_map(|layer| layer.entity_instances.clone())
+            .collect::<Vec<_>>();
+
+        let goal = entities
+            .iter()
+            .find(|entity| entity.identifier == "Goal")
+            .unwrap()
+            .clone();
+        let bouncer = entities
+            .iter_mut()
+            .find(|entity| entity.identifier == "Bouncer")
+            .unwrap();
+
+        bouncer.field_instances[0].value = FieldValue::String(Some(String::from("rich")));
+        bouncer.field_instances.remove(1);
+
+        let bouncer = describe(bouncer);
+
+        entities.retain(|entity| entity.identifier != "Player" || entity.grid.y < 80);
+        entities.push(goal.clone());
+
+        assert_eq!(
+            validate_entities(&entities.iter().collect::<Vec<_>>()),
+            vec![
+                String::from("found 1 Player spawn points, 3 are needed (one per wealth)"),
+                format!(
+                    "there must be one Goal, found 2: {}, {}",
+                    describe(&goal),
+                    describe(&goal)
+                ),
+                format!(
+                    "{bouncer}: invalid `type` rule `rich`: unknown stat `rich`, missing field `push_left`"
+                ),
+            ]
+        );
+    }
+}
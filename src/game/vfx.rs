@@ -1,10 +1,15 @@
 use benimator::{Play, SpriteSheetAnimation};
 use bevy::prelude::*;
+use std::f32::consts::FRAC_PI_2;
 
 use super::{
-    events::{CeilHitEvent, DirectionChangeEvent, JumpEvent, LandingEvent},
+    events::{
+        CeilHitEvent, DirectionChangeEvent, FamilyRescueEvent, JumpEvent, LandingEvent,
+        WallJumpEvent, WallSlideEvent,
+    },
+    physics::PhysicsTime,
     player::PLAYER_HEIGHT_HALF,
-    Animations, Player, PlayerDirection,
+    Animations, Player, PlayerDirection, PLAYER_WIDTH,
 };
 
 #[derive(Component)]
@@ -14,6 +19,8 @@ pub struct Vfx;
 enum AlignVfx {
     Top,
     Bottom,
+    /// On a wall, rotated so the ground of the animation is the wall
+    Side(PlayerDirection),
 }
 
 fn add_vfx(
@@ -24,16 +31,22 @@ fn add_vfx(
     align: AlignVfx,
     flip_x: bool,
 ) {
-    let position = if align == AlignVfx::Top {
-        position + Vec2::new(0.0, PLAYER_HEIGHT_HALF)
-    } else {
-        position - Vec2::new(0.0, PLAYER_HEIGHT_HALF)
+    let (position, rotation) = match align {
+        AlignVfx::Top => (position + Vec2::new(0.0, PLAYER_HEIGHT_HALF), 0.0),
+        AlignVfx::Bottom => (position - Vec2::new(0.0, PLAYER_HEIGHT_HALF), 0.0),
+        AlignVfx::Side(PlayerDirection::Left) => {
+            (position - Vec2::new(PLAYER_WIDTH / 2.0, 0.0), -FRAC_PI_2)
+        }
+        AlignVfx::Side(PlayerDirection::Right) => {
+            (position + Vec2::new(PLAYER_WIDTH / 2.0, 0.0), FRAC_PI_2)
+        }
     };
 
     commands
         .spawn_bundle(SpriteSheetBundle {
             transform: Transform {
                 translation: position.extend(100.0),
+                rotation: Quat::from_rotation_z(rotation),
                 ..Default::default()
             },
             sprite: TextureAtlasSprite {
@@ -48,9 +61,12 @@ fn add_vfx(
         .insert(Vfx);
 }
 
-pub fn blink_player(time: Res<Time>, mut player_query: Query<(&Player, &mut Visibility)>) {
+pub fn blink_player(
+    physics_time: Res<PhysicsTime>,
+    mut player_query: Query<(&Player, &mut Visibility)>,
+) {
     let (player, mut visibility) = player_query.single_mut();
-    let time_seconds = time.seconds_since_startup();
+    let time_seconds = physics_time.elapsed();
 
     if player.blink_until > time_seconds {
         visibility.is_visible = (time_seconds * 10.0) as i32 % 2 == 0;
@@ -103,6 +119,47 @@ pub fn add_landing_dust(
     }
 }
 
+pub fn add_wall_slide_dust(
+    animations: Res<Animations>,
+    mut wall_slide_event: EventReader<WallSlideEvent>,
+    mut commands: Commands,
+) {
+    for ev in wall_slide_event.iter() {
+        add_vfx(
+            &mut commands,
+            animations.vfx_atlas.clone(),
+            animations.vfx_landing_dust.clone(),
+            ev.position,
+            AlignVfx::Side(ev.side),
+            rand::random(),
+        );
+    }
+}
+
+pub fn add_wall_jump_dust(
+    animations: Res<Animations>,
+    mut wall_jump_event: EventReader<WallJumpEvent>,
+    mut commands: Commands,
+) {
+    for ev in wall_jump_event.iter() {
+        // The wall is on the other side of where the jump goes
+        let wall_side = if ev.velocity.x > 0.0 {
+            PlayerDirection::Left
+        } else {
+            PlayerDirection::Right
+        };
+
+        add_vfx(
+            &mut commands,
+            animations.vfx_atlas.clone(),
+            animations.vfx_run_jump_dust.clone(),
+            ev.position,
+            AlignVfx::Side(wall_side),
+            ev.velocity.y > 0.0,
+        );
+    }
+}
+
 pub fn add_direction_change_dust(
     animations: Res<Animations>,
     mut landing_event: EventReader<DirectionChangeEvent>,
@@ -136,3 +193,20 @@ pub fn add_ceil_hit_sprite(
         );
     }
 }
+
+pub fn add_family_rescue_sprite(
+    animations: Res<Animations>,
+    mut family_rescue_event: EventReader<FamilyRescueEvent>,
+    mut commands: Commands,
+) {
+    for ev in family_rescue_event.iter() {
+        add_vfx(
+            &mut commands,
+            animations.vfx_atlas.clone(),
+            animations.vfx_landing_dust.clone(),
+            ev.position,
+            AlignVfx::Bottom,
+            rand::random(),
+        );
+    }
+}
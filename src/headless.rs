@@ -0,0 +1,284 @@
+use benimator::SpriteSheetAnimation;
+use bevy::{
+    asset::AssetPlugin,
+    ecs::{event::Events, system::CommandQueue},
+    prelude::*,
+};
+use bevy_ecs_ldtk::{
+    app::LdtkIntCellMap,
+    ldtk::LdtkJson,
+    utils::{
+        calculate_transform_from_entity_instance, create_entity_definition_map,
+        int_grid_index_to_grid_coords,
+    },
+    EntityInstance, GridCoords, IntGridCell, LevelEvent, LevelSelection,
+};
+use std::fs;
+
+use crate::{
+    controls::ActionsRes,
+    game::{
+        campaign::CampaignRes,
+        goo::Goo,
+        physics::PhysicsTime,
+        replay::{Replay, ReplayRes},
+        run_stats::{RunStats, RunStatsRes},
+        validation::LevelProblemsRes,
+        GameplayPlugin, LdtkObjectsPlugin, Player, Position,
+    },
+    loading::{GameAssets, UIAssets},
+    stats::Stats,
+    tuning::{Tuning, TuningRes, TUNING_PATH},
+    GameState,
+};
+
+pub use crate::game::input::PlayerInput;
+
+const MAP_PATH: &str = "assets/fortuna.ldtk";
+
+/// Runs the game systems without a window, one physics step per `tick`,
+/// feeding a scripted input sequence instead of the keyboard
+pub struct Harness {
+    pub app: App,
+    project: LdtkJson,
+    level: usize,
+}
+
+impl Harness {
+    /// Starts a run of the first level, ready to play, with the character menu already closed
+    pub fn new(stats: Stats, inputs: Vec<PlayerInput>) -> Harness {
+        Harness::from_project(load_project(), stats, inputs)
+    }
+
+    /// Same as `new` with the levels of `project` instead of the game ones
+    pub fn from_project(project: LdtkJson, stats: Stats, inputs: Vec<PlayerInput>) -> Harness {
+        let mut app = App::new();
+
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin)
+            .add_asset::<SpriteSheetAnimation>()
+            .add_state(GameState::MainMenu)
+            .add_event::<LevelEvent>()
+            .init_resource::<ActionsRes>()
+            .init_resource::<Audio>()
+            .init_resource::<WindowDescriptor>()
+            .init_resource::<GameAssets>()
+            .init_resource::<UIAssets>()
+            .add_plugin(LdtkObjectsPlugin)
+            .add_plugin(GameplayPlugin)
+            .insert_resource(PhysicsTime::lockstep())
+            .insert_resource(TuningRes {
+                value: load_tuning(),
+            })
+            .insert_resource(CampaignRes {
+                level_count: project.levels.len(),
+                ..Default::default()
+            })
+            .insert_resource(ReplayRes::new(Some(Replay {
+                tuning: load_tuning(),
+                stats,
+                run_seed: 0,
+                inputs,
+            })));
+
+        // Enters `GameState::Game` from the main menu like the game does, it pushes the character menu
+        app.world
+            .resource_mut::<State<GameState>>()
+            .set(GameState::Game)
+            .unwrap();
+        app.update();
+
+        spawn_level(&mut app.world, &project, 0);
+
+        // Character menu setup (entities & obstacles)
+        app.update();
+
+        // An invalid level goes back to the main menu instead
+        let mut app_state = app.world.resource_mut::<State<GameState>>();
+
+        if *app_state.current() == GameState::CharacterMenu {
+            app_state.pop().unwrap();
+        }
+
+        let mut harness = Harness {
+            app,
+            project,
+            level: 0,
+        };
+
+        // Resumes `GameState::Game`, running the first physics step
+        harness.update();
+        harness
+    }
+
+    /// Advances `ticks` physics steps (none while the next level is spawning)
+    pub fn run(&mut self, ticks: usize) {
+        for _ in 0..ticks {
+            self.update();
+        }
+    }
+
+    /// Advances one update playing `input`, instead of the inputs given on creation
+    pub fn step(&mut self, input: PlayerInput) {
+        self.app
+            .world
+            .resource_mut::<ReplayRes>()
+            .set_next_input(input);
+        self.update();
+    }
+
+    /// Advances until `condition` is met or `max_ticks` are run, returns the ticks run
+    pub fn run_until(&mut self, max_ticks: usize, condition: impl Fn(&Harness) -> bool) -> usize {
+        for tick in 0..max_ticks {
+            if condition(self) {
+                return tick;
+            }
+
+            self.update();
+        }
+
+        max_ticks
+    }
+
+    pub fn level(&self) -> usize {
+        self.app.world.resource::<CampaignRes>().level
+    }
+
+    pub fn state(&self) -> &GameState {
+        self.app.world.resource::<State<GameState>>().current()
+    }
+
+    pub fn run_stats(&self) -> &RunStats {
+        &self.app.world.resource::<RunStatsRes>().value
+    }
+
+    /// Goo surface height and color
+    pub fn goo(&mut self) -> (f32, Color) {
+        let mut query = self.app.world.query::<(&Goo, &Sprite)>();
+        let (goo, sprite) = query
+            .iter(&self.app.world)
+            .next()
+            .expect("The goo spawns with the game");
+
+        (goo.y(), sprite.color)
+    }
+
+    /// Why the level couldn't be played, empty if it could
+    pub fn level_problems(&self) -> &[String] {
+        &self.app.world.resource::<LevelProblemsRes>().value
+    }
+
+    /// `None` once the run ended and the player was despawned
+    pub fn player_lifes(&mut self) -> Option<i32> {
+        let mut query = self.app.world.query::<&Player>();
+
+        query
+            .iter(&self.app.world)
+            .next()
+            .map(|player| player.lifes)
+    }
+
+    pub fn player_position(&mut self) -> Option<Vec2> {
+        let mut query = self.app.world.query_filtered::<&Position, With<Player>>();
+
+        query
+            .iter(&self.app.world)
+            .next()
+            .map(|position| position.value)
+    }
+
+    pub fn teleport_player(&mut self, value: Vec2) {
+        let mut query = self
+            .app
+            .world
+            .query_filtered::<&mut Position, With<Player>>();
+
+        for mut position in query.iter_mut(&mut self.app.world) {
+            position.teleport(value);
+        }
+    }
+
+    fn update(&mut self) {
+        self.app.update();
+
+        // Swap levels like the `LdtkPlugin` does when `LevelSelection` changes
+        if let LevelSelection::Index(level) = *self.app.world.resource::<LevelSelection>() {
+            if level != self.level {
+                despawn_level(&mut self.app.world);
+                spawn_level(&mut self.app.world, &self.project, level);
+                self.level = level;
+            }
+        }
+    }
+}
+
+pub fn load_project() -> LdtkJson {
+    let content =
+        fs::read_to_string(MAP_PATH).unwrap_or_else(|err| panic!("Can't read {MAP_PATH}: {err}"));
+
+    serde_json::from_str(&content).unwrap_or_else(|err| panic!("Can't parse {MAP_PATH}: {err}"))
+}
+
+pub fn load_tuning() -> Tuning {
+    Tuning::from_file(TUNING_PATH).unwrap_or_else(|err| panic!("Can't load {TUNING_PATH}: {err}"))
+}
+
+fn despawn_level(world: &mut World) {
+    let entities = world
+        .query_filtered::<Entity, Or<(With<EntityInstance>, With<GridCoords>)>>()
+        .iter(world)
+        .collect::<Vec<_>>();
+
+    for entity in entities {
+        world.despawn(entity);
+    }
+}
+
+/// What the `LdtkPlugin` would spawn for a level: its entity instances
+/// and the registered int grid cell bundles, then the `LevelEvent::Transformed` event
+fn spawn_level(world: &mut World, project: &LdtkJson, level: usize) {
+    let entity_definitions = create_entity_definition_map(&project.defs.entities);
+    let level = &project.levels[level];
+    let mut queue = CommandQueue::default();
+    let mut commands = Commands::new(&mut queue, world);
+    let int_cell_map = world.non_send_resource::<LdtkIntCellMap>();
+
+    for layer in level.layer_instances.iter().flatten() {
+        for entity_instance in layer.entity_instances.iter() {
+            let transform = calculate_transform_from_entity_instance(
+                entity_instance,
+                &entity_definitions,
+                level.px_hei,
+                0.0,
+            );
+
+            commands
+                .spawn()
+                .insert(transform)
+                .insert(entity_instance.clone());
+        }
+
+        for (index, value) in layer.int_grid_csv.iter().enumerate() {
+            let int_cell = int_cell_map
+                .get(&(Some(layer.identifier.clone()), Some(*value)))
+                .or_else(|| int_cell_map.get(&(None, Some(*value))));
+
+            if let Some(int_cell) = int_cell {
+                let grid_coords =
+                    int_grid_index_to_grid_coords(index, layer.c_wid as u32, layer.c_hei as u32)
+                        .unwrap();
+
+                int_cell.evaluate(
+                    commands.spawn().insert(grid_coords),
+                    IntGridCell { value: *value },
+                    layer,
+                );
+            }
+        }
+    }
+
+    queue.apply(world);
+    world
+        .resource_mut::<Events<LevelEvent>>()
+        .send(LevelEvent::Transformed(level.iid.clone()));
+}
@@ -0,0 +1,302 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+use crate::{
+    game::{replay::ReplayRes, run_stats::RunStatsRes},
+    stats::{Intelligence, MentalHealth, SkinColor, Stats, StatsRes, Strength, Wealth},
+    GameState,
+};
+
+const HISTORY_PATH: &str = "history.ron";
+
+pub struct HistoryPlugin;
+
+impl Plugin for HistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(HistoryRes {
+            value: History::load(),
+        })
+        .add_system_set(SystemSet::on_enter(GameState::WinMenu).with_system(record_win))
+        .add_system_set(SystemSet::on_enter(GameState::LoseMenu).with_system(record_lose));
+    }
+}
+
+/// A finished run: who played it and how it went
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub color: SkinColor,
+    pub mental_health: MentalHealth,
+    pub has_supportive_family: bool,
+    pub intelligence: Intelligence,
+    pub is_male: bool,
+    pub strength: Strength,
+    pub wealth: Wealth,
+    pub won: bool,
+    /// Seconds of play
+    pub time: f64,
+    pub lifes_used: u32,
+}
+
+impl RunRecord {
+    pub fn new(stats: &Stats, won: bool, time: f64, lifes_used: u32) -> RunRecord {
+        RunRecord {
+            color: stats.color,
+            mental_health: stats.mental_health,
+            has_supportive_family: stats.has_supportive_family,
+            intelligence: stats.intelligence,
+            is_male: stats.is_male,
+            strength: stats.strength,
+            wealth: stats.wealth,
+            won,
+            time,
+            lifes_used,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct WinRate {
+    pub label: &'static str,
+    pub wins: usize,
+    pub runs: usize,
+}
+
+impl WinRate {
+    pub fn get_text(&self) -> String {
+        if self.runs == 0 {
+            format!("{} -", self.label)
+        } else {
+            format!(
+                "{} {:.0}% ({}/{})",
+                self.label,
+                self.wins as f32 / self.runs as f32 * 100.0,
+                self.wins,
+                self.runs
+            )
+        }
+    }
+}
+
+/// Every finished run, saved to `history.ron`
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct History {
+    pub runs: Vec<RunRecord>,
+}
+
+impl History {
+    pub fn load() -> History {
+        let content = match fs::read_to_string(HISTORY_PATH) {
+            Ok(content) => content,
+            Err(_) => return History::default(),
+        };
+
+        ron::from_str(&content).unwrap_or_else(|err| {
+            println!("Can't load {HISTORY_PATH}: {err}");
+            History::default()
+        })
+    }
+
+    pub fn save(&self) {
+        let result = ron::ser::to_string(self)
+            .map_err(|err| err.to_string())
+            .and_then(|content| fs::write(HISTORY_PATH, content).map_err(|err| err.to_string()));
+
+        if let Err(err) = result {
+            println!("Can't save {HISTORY_PATH}: {err}");
+        }
+    }
+
+    /// Win rates grouped by each attribute the character is born with
+    pub fn get_report(&self) -> Vec<(&'static str, Vec<WinRate>)> {
+        vec![
+            (
+                "Wealth",
+                self.get_win_rates(
+                    &[
+                        (Wealth::Poor, "Poor"),
+                        (Wealth::MiddleClass, "Middle-class"),
+                        (Wealth::Rich, "Rich"),
+                    ],
+                    |run| run.wealth,
+                ),
+            ),
+            (
+                "Skin color",
+                self.get_win_rates(
+                    &[
+                        (SkinColor::Light, "Light"),
+                        (SkinColor::Medium, "Medium"),
+                        (SkinColor::Dark, "Dark"),
+                    ],
+                    |run| run.color,
+                ),
+            ),
+            (
+                "Gender",
+                self.get_win_rates(&[(true, "Man"), (false, "Woman")], |run| run.is_male),
+            ),
+            (
+                "Mental health",
+                self.get_win_rates(
+                    &[
+                        (MentalHealth::Healthy, "Healthy"),
+                        (MentalHealth::Depressive, "Depressive"),
+                        (MentalHealth::Psychotic, "Psychotic"),
+                    ],
+                    |run| run.mental_health,
+                ),
+            ),
+            (
+                "Strength",
+                self.get_win_rates(
+                    &[(Strength::Weak, "Weak"), (Strength::Strong, "Strong")],
+                    |run| run.strength,
+                ),
+            ),
+            (
+                "Family",
+                self.get_win_rates(&[(true, "Supportive"), (false, "Unstructured")], |run| {
+                    run.has_supportive_family
+                }),
+            ),
+        ]
+    }
+
+    fn get_win_rates<T: PartialEq>(
+        &self,
+        values: &[(T, &'static str)],
+        get_value: impl Fn(&RunRecord) -> T,
+    ) -> Vec<WinRate> {
+        values
+            .iter()
+            .map(|(value, label)| {
+                let runs = self
+                    .runs
+                    .iter()
+                    .filter(|run| get_value(run) == *value)
+                    .collect::<Vec<_>>();
+
+                WinRate {
+                    label,
+                    wins: runs.iter().filter(|run| run.won).count(),
+                    runs: runs.len(),
+                }
+            })
+            .collect()
+    }
+}
+
+pub struct HistoryRes {
+    pub value: History,
+}
+
+fn record_run(
+    won: bool,
+    history: &mut HistoryRes,
+    stats: &StatsRes,
+    run_stats: &RunStatsRes,
+    replay: &ReplayRes,
+) {
+    // Watching a replay isn't playing
+    if replay.playback.is_some() {
+        return;
+    }
+
+    history.value.runs.push(RunRecord::new(
+        &stats.value,
+        won,
+        run_stats.value.time,
+        run_stats.value.goo_hits,
+    ));
+    history.value.save();
+}
+
+fn record_win(
+    mut history: ResMut<HistoryRes>,
+    stats: Res<StatsRes>,
+    run_stats: Res<RunStatsRes>,
+    replay: Res<ReplayRes>,
+) {
+    record_run(true, &mut history, &stats, &run_stats, &replay);
+}
+
+fn record_lose(
+    mut history: ResMut<HistoryRes>,
+    stats: Res<StatsRes>,
+    run_stats: Res<RunStatsRes>,
+    replay: Res<ReplayRes>,
+) {
+    record_run(false, &mut history, &stats, &run_stats, &replay);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tuning::Tuning;
+
+    fn get_run(wealth: Wealth, is_male: bool, won: bool) -> RunRecord {
+        let stats = Stats::from_config(
+            &Tuning::default(),
+            SkinColor::Dark,
+            MentalHealth::Healthy,
+            false,
+            Intelligence::Smart,
+            is_male,
+            Strength::Weak,
+            wealth,
+        );
+
+        RunRecord::new(&stats, won, 60.0, 1)
+    }
+
+    #[test]
+    fn test_get_report() {
+        let history = History {
+            runs: vec![
+                get_run(Wealth::Rich, true, true),
+                get_run(Wealth::Rich, false, true),
+                get_run(Wealth::Poor, true, false),
+                get_run(Wealth::Poor, false, true),
+                get_run(Wealth::Poor, false, false),
+            ],
+        };
+        let report = history.get_report();
+
+        assert_eq!(report.len(), 6);
+        assert_eq!(report[0].0, "Wealth");
+        assert_eq!(
+            report[0]
+                .1
+                .iter()
+                .map(|win_rate| win_rate.get_text())
+                .collect::<Vec<_>>(),
+            ["Poor 33% (1/3)", "Middle-class -", "Rich 100% (2/2)"]
+        );
+        assert_eq!(
+            report[2].1,
+            [
+                WinRate {
+                    label: "Man",
+                    wins: 1,
+                    runs: 2
+                },
+                WinRate {
+                    label: "Woman",
+                    wins: 2,
+                    runs: 3
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_serialize_deserialize() {
+        let history = History {
+            runs: vec![get_run(Wealth::MiddleClass, false, true)],
+        };
+        let content = ron::ser::to_string(&history).unwrap();
+
+        assert_eq!(ron::from_str::<History>(&content).unwrap(), history);
+    }
+}
@@ -0,0 +1,146 @@
+use crate::history::HistoryRes;
+use crate::loading::UIAssets;
+use crate::ui::{handle_ui_buttons, NORMAL_BUTTON};
+use crate::utils::clean_state;
+use crate::GameState;
+use bevy::prelude::*;
+
+pub struct HistoryMenuPlugin;
+
+impl Plugin for HistoryMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_enter(GameState::HistoryMenu).with_system(setup_history_menu),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::HistoryMenu)
+                .with_system(handle_ui_buttons)
+                .with_system(handle_back_button),
+        )
+        .add_system_set(
+            SystemSet::on_exit(GameState::HistoryMenu)
+                .with_system(clean_state::<HistoryMenuStateEntity>),
+        );
+    }
+}
+
+// COMPONENTS
+#[derive(Component)]
+struct HistoryMenuStateEntity;
+
+#[derive(Component)]
+struct BackButton;
+
+// SYSTEMS
+fn setup_history_menu(mut commands: Commands, ui_assets: Res<UIAssets>, history: Res<HistoryRes>) {
+    let runs = &history.value.runs;
+    let wins = runs.iter().filter(|run| run.won).count();
+    let text_style = TextStyle {
+        font: ui_assets.font.clone(),
+        font_size: 20.0,
+        color: Color::BLACK,
+    };
+
+    commands
+        .spawn_bundle(UiCameraBundle::default())
+        .insert(HistoryMenuStateEntity);
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                margin: Rect::all(Val::Px(0.0)),
+                flex_direction: FlexDirection::ColumnReverse,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..Default::default()
+            },
+            color: Color::WHITE.into(),
+            ..Default::default()
+        })
+        .insert(HistoryMenuStateEntity)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                style: Style {
+                    margin: Rect::all(Val::Px(15.0)),
+                    ..Default::default()
+                },
+                text: Text::with_section(
+                    format!("{} runs, {wins} reached the top", runs.len()),
+                    TextStyle {
+                        font: ui_assets.font.clone(),
+                        font_size: 40.0,
+                        color: Color::BLACK,
+                    },
+                    Default::default(),
+                ),
+                ..Default::default()
+            });
+
+            for (group, win_rates) in history.value.get_report() {
+                parent.spawn_bundle(TextBundle {
+                    style: Style {
+                        margin: Rect::all(Val::Px(5.0)),
+                        ..Default::default()
+                    },
+                    text: Text::with_section(
+                        format!(
+                            "{group}: {}",
+                            win_rates
+                                .iter()
+                                .map(|win_rate| win_rate.get_text())
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ),
+                        text_style.clone(),
+                        Default::default(),
+                    ),
+                    ..Default::default()
+                });
+            }
+
+            parent
+                .spawn_bundle(ButtonBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(150.0), Val::Px(45.0)),
+                        margin: Rect {
+                            top: Val::Px(15.0),
+                            right: Val::Undefined,
+                            bottom: Val::Undefined,
+                            left: Val::Undefined,
+                        },
+                        justify_content: JustifyContent::Center, // horizontally center child text
+                        align_items: AlignItems::Center,         // vertically center child text
+                        ..Default::default()
+                    },
+                    color: NORMAL_BUTTON.into(),
+                    ..Default::default()
+                })
+                .insert(BackButton)
+                .with_children(|parent| {
+                    parent.spawn_bundle(TextBundle {
+                        text: Text::with_section(
+                            "Back",
+                            TextStyle {
+                                font: ui_assets.font.clone(),
+                                font_size: 30.0,
+                                color: Color::rgb(0.9, 0.9, 0.9),
+                            },
+                            Default::default(),
+                        ),
+                        ..Default::default()
+                    });
+                });
+        });
+}
+
+fn handle_back_button(
+    mut app_state: ResMut<State<GameState>>,
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<BackButton>)>,
+) {
+    for interaction in interaction_query.iter() {
+        if *interaction == Interaction::Clicked {
+            app_state.set(GameState::MainMenu).unwrap();
+        }
+    }
+}
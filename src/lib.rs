@@ -1,36 +1,77 @@
 mod character_menu;
+mod controls;
+mod controls_menu;
 mod game;
+pub mod headless;
+pub mod history;
+mod history_menu;
 mod loading;
 mod lose_menu;
 mod main_menu;
-mod stats;
+mod pause_menu;
+pub mod stats;
+pub mod tuning;
 mod ui;
 mod utils;
 mod win_menu;
 
 use bevy::prelude::*;
+use game::replay::{Replay, ReplayRes};
+use stats::StatsRes;
+use tuning::Tuning;
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub enum GameState {
     CharacterMenu,
+    ControlsMenu,
     Game,
+    HistoryMenu,
     Loading,
     MainMenu,
     LoseMenu,
+    Paused,
     WinMenu,
 }
 
-pub struct FortunaPlugin;
+#[derive(Default)]
+pub struct FortunaPlugin {
+    /// Start with the character born from this seed
+    pub seed: Option<u64>,
+    /// Replay file to play back instead of reading the keyboard
+    pub replay: Option<String>,
+}
 
 impl Plugin for FortunaPlugin {
     fn build(&self, app: &mut App) {
         app.add_state(GameState::Loading)
             .insert_resource(ClearColor(Color::rgb(1.0, 1.0, 1.0)))
             .add_plugin(character_menu::CharacterMenuPlugin)
+            .add_plugin(controls::ControlsPlugin)
+            .add_plugin(tuning::TuningPlugin)
+            .add_plugin(controls_menu::ControlsMenuPlugin)
             .add_plugin(game::GamePlugin)
+            .add_plugin(history::HistoryPlugin)
+            .add_plugin(history_menu::HistoryMenuPlugin)
             .add_plugin(loading::LoadingPlugin)
             .add_plugin(main_menu::MainMenuPlugin)
             .add_plugin(lose_menu::LoseMenuPlugin)
+            .add_plugin(pause_menu::PauseMenuPlugin)
             .add_plugin(win_menu::WinPlugin);
+
+        if let Some(seed) = self.seed {
+            app.insert_resource(StatsRes::from_seed(seed, &Tuning::default()));
+        }
+
+        if let Some(path) = &self.replay {
+            match Replay::from_file(path) {
+                Ok(replay) => {
+                    app.insert_resource(ReplayRes::new(Some(replay)));
+                }
+                Err(err) => {
+                    eprintln!("Can't load replay {path}: {err}");
+                    std::process::exit(1);
+                }
+            }
+        }
     }
 }
@@ -1,4 +1,4 @@
-use crate::GameState;
+use crate::{tuning::Tuning, GameState};
 use bevy::prelude::*;
 use bevy_asset_loader::{AssetCollection, AssetLoader};
 use bevy_ecs_ldtk::LdtkAsset;
@@ -17,7 +17,7 @@ impl Plugin for LoadingPlugin {
     }
 }
 
-#[derive(AssetCollection)]
+#[derive(AssetCollection, Default)]
 pub struct UIAssets {
     #[asset(path = "goudy-bookletter-1911.otf")]
     pub font: Handle<Font>,
@@ -44,11 +44,11 @@ pub struct UIAssets {
     pub badge_strong: Handle<Image>,
 }
 
-#[derive(AssetCollection)]
+#[derive(AssetCollection, Default)]
 pub struct GameAssets {
     #[asset(path = "fortuna.ldtk")]
     pub map: Handle<LdtkAsset>,
-    #[asset(texture_atlas(tile_size_x = 48.0, tile_size_y = 48.0, columns = 35, rows = 1))]
+    #[asset(texture_atlas(tile_size_x = 48.0, tile_size_y = 48.0, columns = 37, rows = 1))]
     #[asset(path = "player.png")]
     pub player_atlas: Handle<TextureAtlas>,
     #[asset(texture_atlas(tile_size_x = 64.0, tile_size_y = 32.0, columns = 37, rows = 1))]
@@ -56,8 +56,11 @@ pub struct GameAssets {
     pub vfx_atlas: Handle<TextureAtlas>,
     #[asset(path = "jump.ogg")]
     pub jump_sound: Handle<AudioSource>,
+    #[asset(path = "fortuna.tuning.ron")]
+    pub tuning: Handle<Tuning>,
 }
 
-fn hot_reload(_asset_server: Res<AssetServer>) {
-    // asset_server.watch_for_changes().unwrap();
+/// Picks up changes to the assets while the game runs, like the tuning values
+fn hot_reload(asset_server: Res<AssetServer>) {
+    asset_server.watch_for_changes().unwrap();
 }
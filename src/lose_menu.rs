@@ -1,4 +1,11 @@
-use crate::{loading::UIAssets, ui::NORMAL_BUTTON, utils::clean_state, GameState};
+use crate::{
+    game::run_stats::RunStatsRes,
+    loading::UIAssets,
+    stats::StatsRes,
+    ui::{spawn_run_summary, NORMAL_BUTTON},
+    utils::clean_state,
+    GameState,
+};
 use bevy::prelude::*;
 
 pub struct LoseMenuPlugin;
@@ -24,7 +31,12 @@ struct LoseMenuStateEntity;
 struct RetryButton;
 
 // SYSTEMS
-fn setup_score(mut commands: Commands, ui_assets: Res<UIAssets>) {
+fn setup_score(
+    mut commands: Commands,
+    ui_assets: Res<UIAssets>,
+    stats: Res<StatsRes>,
+    run_stats: Res<RunStatsRes>,
+) {
     commands
         .spawn_bundle(UiCameraBundle::default())
         .insert(LoseMenuStateEntity);
@@ -67,6 +79,8 @@ fn setup_score(mut commands: Commands, ui_assets: Res<UIAssets>) {
                 ..Default::default()
             });
 
+            spawn_run_summary(parent, &ui_assets, &stats.value, &run_stats.value);
+
             parent
                 .spawn_bundle(ButtonBundle {
                     style: Style {
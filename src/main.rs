@@ -1,9 +1,19 @@
 use benimator::AnimationPlugin;
 use bevy::prelude::*;
-// use bevy_inspector_egui::WorldInspectorPlugin;
 use doup_fortuna::FortunaPlugin;
 
 fn main() {
+    // `--seed <u64>` to be born as a specific character
+    let seed = get_arg("--seed").map(|seed| match seed.parse::<u64>() {
+        Ok(seed) => seed,
+        Err(err) => {
+            eprintln!("Can't use seed {seed}: {err}");
+            std::process::exit(1);
+        }
+    });
+    // `--replay <file>` to play back a recorded run
+    let replay = get_arg("--replay");
+
     App::new()
         .insert_resource(Msaa { samples: 1 })
         .insert_resource(WindowDescriptor {
@@ -13,8 +23,7 @@ fn main() {
         })
         .add_plugins(DefaultPlugins)
         .add_plugin(AnimationPlugin::default())
-        // .add_plugin(WorldInspectorPlugin::new())
-        .add_plugin(FortunaPlugin)
+        .add_plugin(FortunaPlugin { seed, replay })
         // .add_plugin(bevy_framepace::FramepacePlugin {
         //     enabled: true,
         //     framerate_limit: bevy_framepace::FramerateLimit::Manual(15),
@@ -22,6 +31,9 @@ fn main() {
         //     safety_margin: std::time::Duration::from_micros(100),
         //     power_saver: bevy_framepace::PowerSaver::Disabled,
         // })
-        .add_system(bevy::input::system::exit_on_esc_system)
         .run();
 }
+
+fn get_arg(name: &str) -> Option<String> {
+    std::env::args().skip_while(|arg| arg != name).nth(1)
+}
@@ -1,3 +1,4 @@
+use crate::game::validation::LevelProblemsRes;
 use crate::loading::UIAssets;
 use crate::ui::{handle_ui_buttons, NORMAL_BUTTON};
 use crate::utils::clean_state;
@@ -12,7 +13,9 @@ impl Plugin for MainMenuPlugin {
             .add_system_set(
                 SystemSet::on_update(GameState::MainMenu)
                     .with_system(handle_ui_buttons)
-                    .with_system(handle_play_button),
+                    .with_system(handle_play_button)
+                    .with_system(handle_controls_button)
+                    .with_system(handle_history_button),
             )
             .add_system_set(
                 SystemSet::on_exit(GameState::MainMenu)
@@ -28,8 +31,18 @@ struct MainMenuStateEntity;
 #[derive(Component)]
 struct PlayButton;
 
+#[derive(Component)]
+struct ControlsButton;
+
+#[derive(Component)]
+struct HistoryButton;
+
 // SYSTEMS
-fn setup_main_menu(mut commands: Commands, ui_assets: Res<UIAssets>) {
+fn setup_main_menu(
+    mut commands: Commands,
+    ui_assets: Res<UIAssets>,
+    mut level_problems: ResMut<LevelProblemsRes>,
+) {
     commands
         .spawn_bundle(UiCameraBundle::default())
         .insert(MainMenuStateEntity);
@@ -63,6 +76,36 @@ fn setup_main_menu(mut commands: Commands, ui_assets: Res<UIAssets>) {
                 ..Default::default()
             });
 
+            // Shown once, the next failed level sets them again
+            let problems = std::mem::take(&mut level_problems.value);
+
+            if !problems.is_empty() {
+                parent.spawn_bundle(TextBundle {
+                    style: Style {
+                        max_size: Size::new(Val::Px(600.0), Val::Auto),
+                        margin: Rect::all(Val::Px(15.0)),
+                        ..Default::default()
+                    },
+                    text: Text::with_section(
+                        format!(
+                            "The level can't be played:\n{}",
+                            problems
+                                .iter()
+                                .map(|problem| format!("- {problem}"))
+                                .collect::<Vec<_>>()
+                                .join("\n")
+                        ),
+                        TextStyle {
+                            font: ui_assets.font.clone(),
+                            font_size: 20.0,
+                            color: Color::rgb(0.8, 0.1, 0.1),
+                        },
+                        Default::default(),
+                    ),
+                    ..Default::default()
+                });
+            }
+
             parent
                 .spawn_bundle(ButtonBundle {
                     style: Style {
@@ -95,6 +138,72 @@ fn setup_main_menu(mut commands: Commands, ui_assets: Res<UIAssets>) {
                         ..Default::default()
                     });
                 });
+
+            parent
+                .spawn_bundle(ButtonBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(150.0), Val::Px(45.0)),
+                        margin: Rect {
+                            top: Val::Px(15.0),
+                            right: Val::Undefined,
+                            bottom: Val::Undefined,
+                            left: Val::Undefined,
+                        },
+                        justify_content: JustifyContent::Center, // horizontally center child text
+                        align_items: AlignItems::Center,         // vertically center child text
+                        ..Default::default()
+                    },
+                    color: NORMAL_BUTTON.into(),
+                    ..Default::default()
+                })
+                .insert(ControlsButton)
+                .with_children(|parent| {
+                    parent.spawn_bundle(TextBundle {
+                        text: Text::with_section(
+                            "Controls",
+                            TextStyle {
+                                font: ui_assets.font.clone(),
+                                font_size: 30.0,
+                                color: Color::rgb(0.9, 0.9, 0.9),
+                            },
+                            Default::default(),
+                        ),
+                        ..Default::default()
+                    });
+                });
+
+            parent
+                .spawn_bundle(ButtonBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(150.0), Val::Px(45.0)),
+                        margin: Rect {
+                            top: Val::Px(15.0),
+                            right: Val::Undefined,
+                            bottom: Val::Undefined,
+                            left: Val::Undefined,
+                        },
+                        justify_content: JustifyContent::Center, // horizontally center child text
+                        align_items: AlignItems::Center,         // vertically center child text
+                        ..Default::default()
+                    },
+                    color: NORMAL_BUTTON.into(),
+                    ..Default::default()
+                })
+                .insert(HistoryButton)
+                .with_children(|parent| {
+                    parent.spawn_bundle(TextBundle {
+                        text: Text::with_section(
+                            "History",
+                            TextStyle {
+                                font: ui_assets.font.clone(),
+                                font_size: 30.0,
+                                color: Color::rgb(0.9, 0.9, 0.9),
+                            },
+                            Default::default(),
+                        ),
+                        ..Default::default()
+                    });
+                });
         });
 }
 
@@ -108,3 +217,25 @@ fn handle_play_button(
         }
     }
 }
+
+fn handle_controls_button(
+    mut app_state: ResMut<State<GameState>>,
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<ControlsButton>)>,
+) {
+    for interaction in interaction_query.iter() {
+        if *interaction == Interaction::Clicked {
+            app_state.set(GameState::ControlsMenu).unwrap();
+        }
+    }
+}
+
+fn handle_history_button(
+    mut app_state: ResMut<State<GameState>>,
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<HistoryButton>)>,
+) {
+    for interaction in interaction_query.iter() {
+        if *interaction == Interaction::Clicked {
+            app_state.set(GameState::HistoryMenu).unwrap();
+        }
+    }
+}
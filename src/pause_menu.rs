@@ -0,0 +1,162 @@
+use crate::controls::{Action, ActionsRes};
+use crate::loading::UIAssets;
+use crate::stats::{Stats, StatsRes};
+use crate::tuning::TuningRes;
+use crate::ui::{handle_ui_buttons, NORMAL_BUTTON};
+use crate::utils::clean_state;
+use crate::GameState;
+use bevy::prelude::*;
+
+pub struct PauseMenuPlugin;
+
+impl Plugin for PauseMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(toggle_pause)
+            .add_system_set(SystemSet::on_enter(GameState::Paused).with_system(setup_pause_menu))
+            .add_system_set(
+                SystemSet::on_update(GameState::Paused)
+                    .with_system(handle_ui_buttons)
+                    .with_system(handle_pause_buttons),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Paused)
+                    .with_system(clean_state::<PauseMenuStateEntity>),
+            );
+    }
+}
+
+// COMPONENTS
+#[derive(Component)]
+struct PauseMenuStateEntity;
+
+#[derive(Clone, Copy, Component)]
+enum PauseButton {
+    Resume,
+    /// Same character, from the first level
+    Restart,
+    /// New character, from the first level
+    Reroll,
+    Quit,
+}
+
+impl PauseButton {
+    fn label(&self) -> &str {
+        match self {
+            PauseButton::Resume => "Resume",
+            PauseButton::Restart => "Restart",
+            PauseButton::Reroll => "Re-roll character",
+            PauseButton::Quit => "Quit to menu",
+        }
+    }
+}
+
+// SYSTEMS
+fn setup_pause_menu(mut commands: Commands, ui_assets: Res<UIAssets>) {
+    // The game UI camera is still there, the game is only paused
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::ColumnReverse,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..Default::default()
+            },
+            color: Color::rgba(1.0, 1.0, 1.0, 0.85).into(),
+            ..Default::default()
+        })
+        .insert(PauseMenuStateEntity)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                text: Text::with_section(
+                    "Paused",
+                    TextStyle {
+                        font: ui_assets.font.clone(),
+                        font_size: 60.0,
+                        color: Color::BLACK,
+                    },
+                    Default::default(),
+                ),
+                ..Default::default()
+            });
+
+            for button in [
+                PauseButton::Resume,
+                PauseButton::Restart,
+                PauseButton::Reroll,
+                PauseButton::Quit,
+            ] {
+                parent
+                    .spawn_bundle(ButtonBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(300.0), Val::Px(50.0)),
+                            margin: Rect {
+                                top: Val::Px(15.0),
+                                right: Val::Undefined,
+                                bottom: Val::Undefined,
+                                left: Val::Undefined,
+                            },
+                            justify_content: JustifyContent::Center, // horizontally center child text
+                            align_items: AlignItems::Center,         // vertically center child text
+                            ..Default::default()
+                        },
+                        color: NORMAL_BUTTON.into(),
+                        ..Default::default()
+                    })
+                    .insert(button)
+                    .with_children(|parent| {
+                        parent.spawn_bundle(TextBundle {
+                            text: Text::with_section(
+                                button.label(),
+                                TextStyle {
+                                    font: ui_assets.font.clone(),
+                                    font_size: 30.0,
+                                    color: Color::rgb(0.9, 0.9, 0.9),
+                                },
+                                Default::default(),
+                            ),
+                            ..Default::default()
+                        });
+                    });
+            }
+        });
+}
+
+fn handle_pause_buttons(
+    mut app_state: ResMut<State<GameState>>,
+    mut stats: ResMut<StatsRes>,
+    tuning: Res<TuningRes>,
+    interaction_query: Query<(&Interaction, &PauseButton), Changed<Interaction>>,
+) {
+    for (interaction, button) in interaction_query.iter() {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+
+        match button {
+            PauseButton::Resume => app_state.overwrite_pop().unwrap(),
+            // Replacing the whole stack exits `GameState::Game`, entering it again starts a new run
+            PauseButton::Restart => app_state.overwrite_replace(GameState::Game).unwrap(),
+            PauseButton::Reroll => {
+                *stats = StatsRes::new(Stats::new(&tuning.value));
+                app_state.overwrite_replace(GameState::Game).unwrap();
+            }
+            PauseButton::Quit => app_state.overwrite_replace(GameState::MainMenu).unwrap(),
+        }
+    }
+}
+
+/// Outside of the state systems, which can run twice in the frame the state changes
+/// and would close the menu with the same press that opened it
+fn toggle_pause(actions: Res<ActionsRes>, mut app_state: ResMut<State<GameState>>) {
+    if !actions.just_pressed(Action::Pause) {
+        return;
+    }
+
+    match app_state.current() {
+        GameState::Game => app_state.overwrite_push(GameState::Paused).unwrap(),
+        GameState::Paused => app_state.overwrite_pop().unwrap(),
+        _ => {}
+    }
+}
@@ -1,34 +1,15 @@
+use bevy::prelude::Color;
 use rand::{
     distributions::{Distribution, Standard},
-    Rng,
+    rngs::StdRng,
+    Rng, SeedableRng,
 };
+use serde::{Deserialize, Serialize};
 
-use crate::game::{GRAVITY, TILE_SIZE};
-
-// RUN
-const RUN_TOP_SPEED_STRONG: f32 = 160.0;
-const RUN_TOP_SPEED_WEAK: f32 = 140.0;
-const RUN_TOP_SPEED_DEPRESSED: f32 = 80.0;
-const RUN_TOP_SPEED_TIME: f32 = 100.0; // Time in ms to get to top speed
-const RUN_STOP_TIME: f32 = 50.0; // Time in ms to stop
-const RUN_TOP_SPEED_RATE_STRONG: f32 = RUN_TOP_SPEED_STRONG / (RUN_TOP_SPEED_TIME / 1000.0);
-const RUN_STOP_RATE_STRONG: f32 = RUN_TOP_SPEED_STRONG / (RUN_STOP_TIME / 1000.0);
-const RUN_TOP_SPEED_RATE_WEAK: f32 = RUN_TOP_SPEED_WEAK / (RUN_TOP_SPEED_TIME / 1000.0);
-const RUN_STOP_RATE_WEAK: f32 = RUN_TOP_SPEED_WEAK / (RUN_STOP_TIME / 1000.0);
-const RUN_TOP_SPEED_RATE_DEPRESSED: f32 = RUN_TOP_SPEED_DEPRESSED / (RUN_TOP_SPEED_TIME / 1000.0);
-const RUN_STOP_RATE_DEPRESSED: f32 = RUN_TOP_SPEED_DEPRESSED / (RUN_STOP_TIME / 1000.0);
-
-// JUMP
-const JUMP_CLEARANCE: f32 = 12.0;
-const JUMP_HEIGHT_WEAK_PX: f32 = 5.0 * TILE_SIZE + JUMP_CLEARANCE; // (4 tiles hall + 1 ground) (5 * 16px = 80px) + Clearance
-const JUMP_HEIGHT_STRONG_PX: f32 = 6.0 * TILE_SIZE + JUMP_CLEARANCE; // +0.75 tile
-const JUMP_HEIGHT_DEPRESSED_PX: f32 = 5.0 * TILE_SIZE + 6.0; // Barely any clearance (2px) for 5 tiles
-
-// DEPRESSIVE STATE
-const MIN_DEPRE_CHANCE: f64 = 0.15;
-const MAX_DEPRE_CHANCE: f64 = 0.60;
-
-#[derive(Debug, PartialEq)]
+use crate::{game::GRAVITY, tuning::Tuning};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "dev", derive(bevy_inspector_egui::Inspectable))]
 pub enum Wealth {
     Poor,
     MiddleClass,
@@ -45,7 +26,8 @@ impl Distribution<Wealth> for Standard {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "dev", derive(bevy_inspector_egui::Inspectable))]
 pub enum MentalHealth {
     Healthy,
     Depressive,
@@ -62,7 +44,8 @@ impl Distribution<MentalHealth> for Standard {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "dev", derive(bevy_inspector_egui::Inspectable))]
 pub enum Strength {
     Weak,
     Strong,
@@ -77,13 +60,25 @@ impl Distribution<Strength> for Standard {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "dev", derive(bevy_inspector_egui::Inspectable))]
 pub enum SkinColor {
     Light,
     Medium,
     Dark,
 }
 
+impl SkinColor {
+    /// Replaces the head color of the player sprite, `Light` is the one drawn
+    pub fn get_tone(&self) -> Color {
+        match self {
+            SkinColor::Light => Color::rgb_u8(255, 204, 208),
+            SkinColor::Medium => Color::rgb_u8(214, 150, 108),
+            SkinColor::Dark => Color::rgb_u8(124, 78, 56),
+        }
+    }
+}
+
 impl Distribution<SkinColor> for Standard {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> SkinColor {
         match rng.gen_range(0..=2) {
@@ -94,7 +89,8 @@ impl Distribution<SkinColor> for Standard {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "dev", derive(bevy_inspector_egui::Inspectable))]
 pub enum Intelligence {
     Dumb,
     Smart,
@@ -111,10 +107,34 @@ impl Distribution<Intelligence> for Standard {
 
 pub struct StatsRes {
     pub value: Stats,
+    /// Gameplay RNG, seeded from `Stats.seed` when there is one
+    pub rng: StdRng,
 }
 
-#[derive(Debug)]
+impl StatsRes {
+    pub fn new(value: Stats) -> StatsRes {
+        let rng = match value.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+
+        StatsRes { value, rng }
+    }
+
+    /// Same seed, same character and same in-game rolls
+    pub fn from_seed(seed: u64, tuning: &Tuning) -> StatsRes {
+        StatsRes::new(Stats::from_seed(seed, tuning))
+    }
+
+    pub fn reseed_rng(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "dev", derive(bevy_inspector_egui::Inspectable))]
 pub struct Stats {
+    pub seed: Option<u64>,
     pub color: SkinColor,
     pub mental_health: MentalHealth,
     pub has_supportive_family: bool,
@@ -125,7 +145,11 @@ pub struct Stats {
     // Computed
     pub is_depressive: bool,
     pub depre_chance: f64,
+    pub is_psychotic: bool,
+    pub psychosis_chance: f64,
     pub can_skip_one_way_platforms: bool,
+    pub can_drop_through_platforms: bool,
+    pub can_use_checkpoints: bool,
     pub top_speed: f32,
     pub top_speed_depressed: f32,
     pub top_speed_rate: f32,
@@ -134,23 +158,59 @@ pub struct Stats {
     pub stop_rate_depressed: f32,
     pub jump_force: f32,
     pub jump_force_depressed: f32,
+    /// Upward velocity kept when Jump is released early
+    pub jump_min_force: f32,
+    pub jump_min_force_depressed: f32,
+    /// Max fall speed while sliding down a wall
+    pub wall_slide_speed: f32,
+    pub wall_slide_speed_depressed: f32,
+    pub wall_jump_force: f32,
+    pub wall_jump_force_depressed: f32,
+    /// Horizontal push away from the wall
+    pub wall_jump_kick: f32,
+    pub wall_jump_kick_depressed: f32,
     pub lifes: i32,
+    /// Times the family catches the player before the goo, per run
+    pub family_rescues: u32,
+    pub family_rescue_force: f32,
+    /// Seconds of the jump arc shown ahead, none for `jump_preview_time` 0
+    pub jump_preview_time: f32,
+    pub skin_tone: Color,
 }
 
 impl Stats {
-    pub fn new() -> Stats {
-        Stats::from_config(
-            rand::random(),
-            rand::random(),
-            rand::random(),
-            rand::random(),
-            rand::random(),
-            rand::random(),
-            rand::random(),
-        )
+    pub fn new(tuning: &Tuning) -> Stats {
+        Stats::from_seed(rand::random(), tuning)
+    }
+
+    pub fn from_seed(seed: u64, tuning: &Tuning) -> Stats {
+        let rng = &mut StdRng::seed_from_u64(seed);
+        let color = rng.gen();
+        let mental_health = rng.gen();
+        let has_supportive_family = rng.gen();
+        let intelligence = rng.gen();
+        let is_male = rng.gen();
+        let strength = rng.gen();
+        let wealth = rng.gen();
+
+        let mut stats = Stats::from_config_with_rng(
+            rng,
+            tuning,
+            color,
+            mental_health,
+            has_supportive_family,
+            intelligence,
+            is_male,
+            strength,
+            wealth,
+        );
+
+        stats.seed = Some(seed);
+        stats
     }
 
     pub fn from_config(
+        tuning: &Tuning,
         color: SkinColor,
         mental_health: MentalHealth,
         has_supportive_family: bool,
@@ -159,42 +219,40 @@ impl Stats {
         strength: Strength,
         wealth: Wealth,
     ) -> Stats {
-        let is_depressive = mental_health == MentalHealth::Depressive;
-        let depre_chance = rand::thread_rng().gen_range(MIN_DEPRE_CHANCE..MAX_DEPRE_CHANCE);
-        let can_skip_one_way_platforms = is_male;
-        let top_speed_depressed = RUN_TOP_SPEED_DEPRESSED;
-        let top_speed_rate_depressed = RUN_TOP_SPEED_RATE_DEPRESSED;
-        let stop_rate_depressed = RUN_STOP_RATE_DEPRESSED;
-        let jump_force_depressed = (-2.0 * GRAVITY * JUMP_HEIGHT_DEPRESSED_PX).sqrt();
-        let top_speed;
-        let top_speed_rate;
-        let stop_rate;
-        let jump_force;
-
-        if strength == Strength::Strong {
-            top_speed = RUN_TOP_SPEED_STRONG;
-            top_speed_rate = RUN_TOP_SPEED_RATE_STRONG;
-            stop_rate = RUN_STOP_RATE_STRONG;
-            jump_force = (-2.0 * GRAVITY * JUMP_HEIGHT_STRONG_PX).sqrt();
-        } else {
-            top_speed = RUN_TOP_SPEED_WEAK;
-            top_speed_rate = RUN_TOP_SPEED_RATE_WEAK;
-            stop_rate = RUN_STOP_RATE_WEAK;
-            jump_force = (-2.0 * GRAVITY * JUMP_HEIGHT_WEAK_PX).sqrt();
-        }
+        Stats::from_config_with_rng(
+            &mut rand::thread_rng(),
+            tuning,
+            color,
+            mental_health,
+            has_supportive_family,
+            intelligence,
+            is_male,
+            strength,
+            wealth,
+        )
+    }
 
+    #[allow(clippy::too_many_arguments)]
+    fn from_config_with_rng<R: Rng + ?Sized>(
+        rng: &mut R,
+        tuning: &Tuning,
+        color: SkinColor,
+        mental_health: MentalHealth,
+        has_supportive_family: bool,
+        intelligence: Intelligence,
+        is_male: bool,
+        strength: Strength,
+        wealth: Wealth,
+    ) -> Stats {
         // Lifes
-        let mut lifes = match wealth {
+        let lifes = match wealth {
             Wealth::Rich => 3,
             Wealth::MiddleClass => 2,
             Wealth::Poor => 1,
         };
 
-        if has_supportive_family && lifes < 3 {
-            lifes += 1;
-        }
-
-        Stats {
+        let mut stats = Stats {
+            seed: None,
             color,
             mental_health,
             has_supportive_family,
@@ -203,19 +261,96 @@ impl Stats {
             strength,
             wealth,
             // Computed
-            can_skip_one_way_platforms,
-            depre_chance,
-            is_depressive,
-            jump_force,
-            jump_force_depressed,
+            can_skip_one_way_platforms: is_male,
+            can_drop_through_platforms: true,
+            can_use_checkpoints: true,
+            depre_chance: rng.gen_range(tuning.min_depre_chance..tuning.max_depre_chance),
+            is_depressive: mental_health == MentalHealth::Depressive,
+            psychosis_chance: rng
+                .gen_range(tuning.min_psychosis_chance..tuning.max_psychosis_chance),
+            is_psychotic: mental_health == MentalHealth::Psychotic,
+            jump_force: 0.0,
+            jump_force_depressed: 0.0,
+            jump_min_force: 0.0,
+            jump_min_force_depressed: 0.0,
+            wall_slide_speed: 0.0,
+            wall_slide_speed_depressed: 0.0,
+            wall_jump_force: 0.0,
+            wall_jump_force_depressed: 0.0,
+            wall_jump_kick: 0.0,
+            wall_jump_kick_depressed: 0.0,
             lifes,
-            stop_rate,
-            stop_rate_depressed,
-            top_speed,
-            top_speed_depressed,
-            top_speed_rate,
-            top_speed_rate_depressed,
+            family_rescues: 0,
+            family_rescue_force: 0.0,
+            jump_preview_time: 0.0,
+            skin_tone: color.get_tone(),
+            stop_rate: 0.0,
+            stop_rate_depressed: 0.0,
+            top_speed: 0.0,
+            top_speed_depressed: 0.0,
+            top_speed_rate: 0.0,
+            top_speed_rate_depressed: 0.0,
+        };
+
+        stats.apply_tuning(tuning);
+        stats
+    }
+
+    /// Computes the gameplay values, `depre_chance` and `psychosis_chance` are kept as rolled
+    /// when the character was born
+    pub fn apply_tuning(&mut self, tuning: &Tuning) {
+        let jump_force = |height: f32| (-2.0 * GRAVITY * height).sqrt();
+
+        self.top_speed_depressed = tuning.run_top_speed_depressed;
+        self.top_speed_rate_depressed = tuning.run_top_speed_depressed / tuning.run_top_speed_time;
+        self.stop_rate_depressed = tuning.run_top_speed_depressed / tuning.run_stop_time;
+        self.jump_force_depressed = jump_force(tuning.jump_height_depressed);
+        self.jump_min_force_depressed = jump_force(tuning.jump_min_height_depressed);
+        self.wall_slide_speed_depressed = tuning.wall_slide_speed_depressed;
+        self.wall_jump_force_depressed = jump_force(tuning.wall_jump_height_depressed);
+        self.wall_jump_kick_depressed = tuning.wall_jump_kick_depressed;
+
+        if self.strength == Strength::Strong {
+            self.top_speed = tuning.run_top_speed_strong;
+            self.jump_force = jump_force(tuning.jump_height_strong);
+            self.jump_min_force = jump_force(tuning.jump_min_height_strong);
+            self.wall_slide_speed = tuning.wall_slide_speed_strong;
+            self.wall_jump_force = jump_force(tuning.wall_jump_height_strong);
+            self.wall_jump_kick = tuning.wall_jump_kick_strong;
+        } else {
+            self.top_speed = tuning.run_top_speed_weak;
+            self.jump_force = jump_force(tuning.jump_height_weak);
+            self.jump_min_force = jump_force(tuning.jump_min_height_weak);
+            self.wall_slide_speed = tuning.wall_slide_speed_weak;
+            self.wall_jump_force = jump_force(tuning.wall_jump_height_weak);
+            self.wall_jump_kick = tuning.wall_jump_kick_weak;
         }
+
+        self.top_speed_rate = self.top_speed / tuning.run_top_speed_time;
+        self.stop_rate = self.top_speed / tuning.run_stop_time;
+
+        // Smart characters see where they will land
+        self.jump_preview_time = match self.intelligence {
+            Intelligence::Smart => tuning.jump_preview_time_smart,
+            Intelligence::Dumb => tuning.jump_preview_time_dumb,
+        };
+        self.skin_tone = self.color.get_tone();
+
+        self.family_rescues = if self.has_supportive_family {
+            tuning.family_rescues
+        } else {
+            0
+        };
+        self.family_rescue_force = jump_force(tuning.family_rescue_height);
+
+        self.can_drop_through_platforms = match &tuning.drop_through_rule {
+            Some(rule) => rule.eval(self),
+            None => true,
+        };
+        self.can_use_checkpoints = match &tuning.checkpoint_rule {
+            Some(rule) => rule.eval(self),
+            None => true,
+        };
     }
 
     pub fn get_description(&self) -> String {
@@ -231,6 +366,7 @@ impl Stats {
 
         let mental_health = match self.mental_health {
             MentalHealth::Healthy => String::from("mentally healthy, "),
+            MentalHealth::Psychotic => String::from("prone to psychotic episodes, "),
             _ => String::from(""),
         };
 
@@ -270,6 +406,7 @@ mod test {
     #[test]
     fn test_get_description() {
         let stats = Stats::from_config(
+            &Tuning::default(),
             SkinColor::Light,
             MentalHealth::Healthy,
             true,
@@ -282,6 +419,7 @@ mod test {
         assert_eq!("You're a man born to a rich supportive family. You're mentally healthy, physically strong and fairly smart.", stats.get_description());
 
         let stats = Stats::from_config(
+            &Tuning::default(),
             SkinColor::Light,
             MentalHealth::Depressive,
             false,
@@ -294,6 +432,7 @@ mod test {
         assert_eq!("You're a woman born to a poor unstructured family. You're not very strong and not very smart.", stats.get_description());
 
         let stats = Stats::from_config(
+            &Tuning::default(),
             SkinColor::Light,
             MentalHealth::Healthy,
             true,
@@ -304,5 +443,34 @@ mod test {
         );
 
         assert_eq!("You're a woman born to a middle-class supportive family. You're mentally healthy, not very strong but you're fairly smart.", stats.get_description());
+
+        let stats = Stats::from_config(
+            &Tuning::default(),
+            SkinColor::Dark,
+            MentalHealth::Psychotic,
+            false,
+            Intelligence::Smart,
+            true,
+            Strength::Weak,
+            Wealth::Rich,
+        );
+
+        assert_eq!("You're a man born to a rich unstructured family. You're prone to psychotic episodes, not very strong but you're fairly smart.", stats.get_description());
+    }
+
+    #[test]
+    fn test_from_seed() {
+        assert_eq!(
+            Stats::from_seed(1234, &Tuning::default()),
+            Stats::from_seed(1234, &Tuning::default())
+        );
+        assert_eq!(Stats::from_seed(1234, &Tuning::default()).seed, Some(1234));
+
+        let mut a = StatsRes::from_seed(42, &Tuning::default());
+        let mut b = StatsRes::from_seed(42, &Tuning::default());
+
+        assert_eq!(a.value, b.value);
+        assert_eq!(a.value, Stats::from_seed(42, &Tuning::default()));
+        assert_eq!(a.rng.gen::<f64>(), b.rng.gen::<f64>());
     }
 }
@@ -0,0 +1,276 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+use crate::{
+    game::{replay::ReplayRes, rule::Rule, TILE_SIZE},
+    loading::GameAssets,
+    stats::StatsRes,
+};
+
+pub const TUNING_PATH: &str = "assets/fortuna.tuning.ron";
+
+pub struct TuningPlugin;
+
+impl Plugin for TuningPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<Tuning>()
+            .init_asset_loader::<TuningLoader>()
+            .init_resource::<TuningRes>()
+            .add_system(update_tuning);
+    }
+}
+
+/// Balance numbers, loaded from `assets/fortuna.tuning.ron`
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize, TypeUuid)]
+#[uuid = "53a2a22a-7b90-4abc-92c2-805a87851ff0"]
+pub struct Tuning {
+    // Run, speeds in px/s
+    pub run_top_speed_strong: f32,
+    pub run_top_speed_weak: f32,
+    pub run_top_speed_depressed: f32,
+    /// Seconds to get to top speed
+    pub run_top_speed_time: f32,
+    /// Seconds to stop
+    pub run_stop_time: f32,
+    // Jump, heights in px
+    pub jump_height_strong: f32,
+    pub jump_height_weak: f32,
+    pub jump_height_depressed: f32,
+    /// Height when releasing Jump right away
+    pub jump_min_height_strong: f32,
+    pub jump_min_height_weak: f32,
+    pub jump_min_height_depressed: f32,
+    /// Seconds after falling from a platform that still can jump
+    pub coyote_time: f64,
+    /// Seconds before touching ground that jump will be valid
+    pub jump_buffer_time: f64,
+    /// Part of the gravity applied while sliding down a wall
+    pub wall_slide_gravity: f32,
+    // Wall slide, max fall speeds in px/s
+    pub wall_slide_speed_strong: f32,
+    pub wall_slide_speed_weak: f32,
+    pub wall_slide_speed_depressed: f32,
+    // Wall jump, heights in px and horizontal kicks in px/s
+    pub wall_jump_height_strong: f32,
+    pub wall_jump_height_weak: f32,
+    pub wall_jump_height_depressed: f32,
+    pub wall_jump_kick_strong: f32,
+    pub wall_jump_kick_weak: f32,
+    pub wall_jump_kick_depressed: f32,
+    /// Seconds of the jump arc previewed while on the ground, 0 to hide it
+    pub jump_preview_time_smart: f32,
+    pub jump_preview_time_dumb: f32,
+    /// Seconds one-way platforms are ignored after Down+Jump
+    pub drop_through_time: f64,
+    /// Rule of who can drop through one-way platforms, everyone if `None`
+    pub drop_through_allow: Option<String>,
+    /// Rule of who can use checkpoints, everyone if `None`
+    pub checkpoint_allow: Option<String>,
+    /// `drop_through_allow` parsed by `Tuning::parse`
+    #[serde(skip)]
+    pub drop_through_rule: Option<Rule>,
+    /// `checkpoint_allow` parsed by `Tuning::parse`
+    #[serde(skip)]
+    pub checkpoint_rule: Option<Rule>,
+    // Depression, chance rolled once per character
+    pub min_depre_chance: f64,
+    pub max_depre_chance: f64,
+    // Psychosis, chance rolled once per character
+    pub min_psychosis_chance: f64,
+    pub max_psychosis_chance: f64,
+    /// Seconds an episode lasts, rolled between both
+    pub min_psychosis_duration: f64,
+    pub max_psychosis_duration: f64,
+    /// Seconds after an episode before the next one can start
+    pub psychosis_cooldown: f64,
+    // Goo, px/s and px
+    pub goo_speed: f32,
+    pub goo_hit_regress: f32,
+    /// Player height above the goo from which it speeds up, 0 to never.
+    /// Levels can override the goo settings, see `goo::GooSettings`.
+    pub goo_catch_up_distance: f32,
+    /// Px/s added once the player is twice `goo_catch_up_distance` above
+    pub goo_catch_up_speed: f32,
+    /// Times a supportive family catches the player before the goo
+    pub family_rescues: u32,
+    /// Height in px the player is thrown back up when caught
+    pub family_rescue_height: f32,
+    // Bouncer
+    pub bouncer_force: f32,
+    /// Seconds the push lasts
+    pub bouncer_duration: f32,
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        let jump_clearance = 12.0;
+
+        Tuning {
+            run_top_speed_strong: 160.0,
+            run_top_speed_weak: 140.0,
+            run_top_speed_depressed: 80.0,
+            run_top_speed_time: 0.1,
+            run_stop_time: 0.05,
+            jump_height_strong: 6.0 * TILE_SIZE + jump_clearance,
+            jump_height_weak: 5.0 * TILE_SIZE + jump_clearance, // 4 tiles hall + 1 ground
+            jump_height_depressed: 5.0 * TILE_SIZE + 6.0,       // Barely any clearance for 5 tiles
+            jump_min_height_strong: 2.5 * TILE_SIZE,
+            jump_min_height_weak: 2.0 * TILE_SIZE,
+            jump_min_height_depressed: 1.5 * TILE_SIZE,
+            coyote_time: 0.125,
+            jump_buffer_time: 0.1,
+            wall_slide_gravity: 0.3,
+            wall_slide_speed_strong: 60.0,
+            wall_slide_speed_weak: 90.0,
+            wall_slide_speed_depressed: 140.0,
+            wall_jump_height_strong: 4.0 * TILE_SIZE,
+            wall_jump_height_weak: 3.0 * TILE_SIZE,
+            wall_jump_height_depressed: 2.0 * TILE_SIZE,
+            wall_jump_kick_strong: 220.0,
+            wall_jump_kick_weak: 180.0,
+            wall_jump_kick_depressed: 120.0,
+            jump_preview_time_smart: 0.6,
+            jump_preview_time_dumb: 0.0,
+            drop_through_time: 0.25,
+            drop_through_allow: None,
+            checkpoint_allow: None,
+            drop_through_rule: None,
+            checkpoint_rule: None,
+            min_depre_chance: 0.15,
+            max_depre_chance: 0.60,
+            min_psychosis_chance: 0.10,
+            max_psychosis_chance: 0.40,
+            min_psychosis_duration: 3.0,
+            max_psychosis_duration: 8.0,
+            psychosis_cooldown: 15.0,
+            goo_speed: 32.0,
+            goo_hit_regress: 64.0,
+            goo_catch_up_distance: 0.0,
+            goo_catch_up_speed: 0.0,
+            family_rescues: 1,
+            family_rescue_height: 8.0 * TILE_SIZE,
+            bouncer_force: 2500.0,
+            bouncer_duration: 0.5,
+        }
+    }
+}
+
+impl Tuning {
+    pub fn parse(content: &str) -> Result<Tuning, String> {
+        let mut tuning: Tuning = ron::from_str(content).map_err(|err| err.to_string())?;
+        let parse_rule = |name: &str, source: &Option<String>| match source {
+            Some(source) => Rule::parse(source)
+                .map(Some)
+                .map_err(|err| format!("`{name}`: {err}")),
+            None => Ok(None),
+        };
+
+        tuning.drop_through_rule = parse_rule("drop_through_allow", &tuning.drop_through_allow)?;
+        tuning.checkpoint_rule = parse_rule("checkpoint_allow", &tuning.checkpoint_allow)?;
+
+        Ok(tuning)
+    }
+
+    /// For headless runs, which don't load assets
+    pub fn from_file(path: &str) -> Result<Tuning, String> {
+        Tuning::parse(&fs::read_to_string(path).map_err(|err| err.to_string())?)
+    }
+}
+
+#[derive(Default)]
+pub struct TuningLoader;
+
+impl AssetLoader for TuningLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let tuning = Tuning::parse(std::str::from_utf8(bytes)?).map_err(anyhow::Error::msg)?;
+
+            load_context.set_default_asset(LoadedAsset::new(tuning));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tuning.ron"]
+    }
+}
+
+/// Tuning in use, it's the default one until the asset loads
+#[derive(Default)]
+pub struct TuningRes {
+    pub value: Tuning,
+}
+
+/// Copies the asset when it loads or changes on disk, the character gets the new values.
+/// A replay keeps the tuning it was recorded with.
+fn update_tuning(
+    game_assets: Option<Res<GameAssets>>,
+    tunings: Res<Assets<Tuning>>,
+    replay: Res<ReplayRes>,
+    mut events: EventReader<AssetEvent<Tuning>>,
+    mut tuning: ResMut<TuningRes>,
+    mut stats: ResMut<StatsRes>,
+) {
+    // Loaded with the other game assets, only that tuning file is the game one
+    let game_assets = match game_assets {
+        Some(game_assets) => game_assets,
+        None => return,
+    };
+    let is_first_load = game_assets.is_added();
+    let is_modified = events.iter().any(
+        |event| matches!(event, AssetEvent::Modified { handle } if *handle == game_assets.tuning),
+    );
+
+    if !is_first_load && (!is_modified || replay.playback.is_some()) {
+        return;
+    }
+
+    if let Some(value) = tunings.get(&game_assets.tuning) {
+        tuning.value = value.clone();
+
+        match stats.value.seed {
+            // Born with `--seed` before the tuning loaded, roll its chances again
+            Some(seed) if is_first_load => *stats = StatsRes::from_seed(seed, &tuning.value),
+            _ => stats.value.apply_tuning(&tuning.value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_tuning_file() {
+        let tuning = Tuning::from_file(TUNING_PATH).unwrap();
+
+        // Rules are parsed once, with the tuning
+        assert_eq!(
+            tuning.checkpoint_rule,
+            Some(Rule::parse("wealth>=middle").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_invalid_rule() {
+        let content = fs::read_to_string(TUNING_PATH).unwrap().replace(
+            "drop_through_allow: None",
+            "drop_through_allow: Some(\"is_tall\")",
+        );
+
+        assert_eq!(
+            Tuning::parse(&content),
+            Err(String::from("`drop_through_allow`: unknown stat `is_tall`"))
+        );
+    }
+}
@@ -1,5 +1,7 @@
 use bevy::prelude::*;
 
+use crate::{game::run_stats::RunStats, loading::UIAssets, stats::Stats};
+
 pub const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
 pub const HOVERED_BUTTON: Color = Color::rgb(0.25, 0.25, 0.25);
 pub const PRESSED_BUTTON: Color = Color::rgb(0.35, 0.75, 0.35);
@@ -24,3 +26,34 @@ pub fn handle_ui_buttons(
         }
     }
 }
+
+/// Character description and how the run went, shown when it ends
+pub fn spawn_run_summary(
+    parent: &mut ChildBuilder,
+    ui_assets: &UIAssets,
+    stats: &Stats,
+    run_stats: &RunStats,
+) {
+    parent.spawn_bundle(TextBundle {
+        style: Style {
+            max_size: Size::new(Val::Px(600.0), Val::Auto),
+            margin: Rect::all(Val::Px(15.0)),
+            ..Default::default()
+        },
+        text: Text {
+            alignment: TextAlignment {
+                horizontal: HorizontalAlign::Center,
+                vertical: VerticalAlign::Center,
+            },
+            sections: vec![TextSection {
+                value: format!("{}\n\n{}", stats.get_description(), run_stats.get_summary()),
+                style: TextStyle {
+                    font: ui_assets.font.clone(),
+                    font_size: 20.0,
+                    color: Color::BLACK,
+                },
+            }],
+        },
+        ..Default::default()
+    });
+}
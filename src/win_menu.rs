@@ -1,5 +1,7 @@
+use crate::game::run_stats::RunStatsRes;
 use crate::loading::UIAssets;
-use crate::ui::{handle_ui_buttons, NORMAL_BUTTON};
+use crate::stats::StatsRes;
+use crate::ui::{handle_ui_buttons, spawn_run_summary, NORMAL_BUTTON};
 use crate::utils::clean_state;
 use crate::GameState;
 use bevy::prelude::*;
@@ -29,7 +31,12 @@ struct WinMenuStateEntity;
 struct PlayAgainButton;
 
 // SYSTEMS
-fn setup_win(mut commands: Commands, ui_assets: Res<UIAssets>) {
+fn setup_win(
+    mut commands: Commands,
+    ui_assets: Res<UIAssets>,
+    stats: Res<StatsRes>,
+    run_stats: Res<RunStatsRes>,
+) {
     commands
         .spawn_bundle(UiCameraBundle::default())
         .insert(WinMenuStateEntity);
@@ -72,6 +79,8 @@ fn setup_win(mut commands: Commands, ui_assets: Res<UIAssets>) {
                 ..Default::default()
             });
 
+            spawn_run_summary(parent, &ui_assets, &stats.value, &run_stats.value);
+
             parent
                 .spawn_bundle(ButtonBundle {
                     style: Style {
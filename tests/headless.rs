@@ -0,0 +1,596 @@
+use bevy::{math::const_ivec2, prelude::*};
+use bevy_ecs_ldtk::ldtk::{FieldInstance, FieldValue, LdtkJson};
+use doup_fortuna::{
+    headless::{load_project, load_tuning, Harness, PlayerInput},
+    stats::{Intelligence, MentalHealth, SkinColor, Stats, Strength, Wealth},
+    GameState,
+};
+
+const LEFT: PlayerInput = PlayerInput {
+    left: true,
+    right: false,
+    jump: false,
+    jump_held: false,
+    down: false,
+};
+
+fn get_stats() -> Stats {
+    Stats::from_config(
+        &load_tuning(),
+        SkinColor::Light,
+        MentalHealth::Healthy,
+        true,
+        Intelligence::Smart,
+        true,
+        Strength::Weak,
+        Wealth::Rich,
+    )
+}
+
+/// Adds a 3x1 tiles entity to the first level at `grid`
+fn add_entity(
+    project: &mut LdtkJson,
+    identifier: &str,
+    grid: IVec2,
+    fields: Vec<(&str, FieldValue)>,
+) {
+    let definition = project
+        .defs
+        .entities
+        .iter()
+        .find(|definition| definition.identifier == identifier)
+        .unwrap()
+        .clone();
+    let layer = project.levels[0]
+        .layer_instances
+        .iter_mut()
+        .flatten()
+        .find(|layer| layer.identifier == "Entities")
+        .unwrap();
+    let mut platform = layer
+        .entity_instances
+        .iter()
+        .find(|entity| entity.identifier == "Goal")
+        .unwrap()
+        .clone();
+
+    platform.identifier = String::from(identifier);
+    platform.iid = format!("{identifier}-test");
+    platform.def_uid = definition.uid;
+    platform.grid = grid;
+    platform.px = platform.grid * 16;
+    platform.width = 48;
+    platform.height = 16;
+    platform.field_instances = fields
+        .into_iter()
+        .map(|(identifier, value)| {
+            let field = definition
+                .field_defs
+                .iter()
+                .find(|field| field.identifier == identifier)
+                .unwrap();
+
+            FieldInstance {
+                identifier: String::from(identifier),
+                tile: None,
+                field_instance_type: field.field_definition_type.clone(),
+                value,
+                def_uid: field.uid,
+                real_editor_values: vec![],
+            }
+        })
+        .collect();
+
+    layer.entity_instances.push(platform);
+}
+
+/// Changes the fields of the `GooSettings` entity of the first level
+fn set_goo_settings(project: &mut LdtkJson, fields: Vec<(&str, FieldValue)>) {
+    let settings = project.levels[0]
+        .layer_instances
+        .iter_mut()
+        .flatten()
+        .flat_map(|layer| layer.entity_instances.iter_mut())
+        .find(|entity| entity.identifier == "GooSettings")
+        .unwrap();
+
+    for (identifier, value) in fields {
+        settings
+            .field_instances
+            .iter_mut()
+            .find(|field| field.identifier == identifier)
+            .unwrap()
+            .value = value;
+    }
+}
+
+/// Empty area left of the top of the first level
+const PLATFORM_GRID: IVec2 = const_ivec2!([12, 5]);
+
+/// Standing on a platform at `PLATFORM_GRID`
+const ON_PLATFORM_X: f32 = 216.0;
+const ON_PLATFORM_Y: f32 = 1378.0;
+
+#[test]
+fn test_goo_takes_lifes() {
+    let mut harness = Harness::new(get_stats(), vec![]);
+    let lifes = harness.player_lifes().unwrap();
+
+    harness.run_until(3000, |harness| *harness.state() != GameState::Game);
+
+    assert!(lifes > 0);
+    assert_eq!(*harness.state(), GameState::LoseMenu);
+    assert_eq!(harness.player_lifes(), None);
+    assert_eq!(harness.run_stats().goo_hits, lifes as u32);
+    // Supportive family, caught once before losing any life
+    assert_eq!(harness.run_stats().family_rescues, 1);
+    assert_eq!(harness.run_stats().jumps, 0);
+    assert!(harness.run_stats().time > 0.0);
+}
+
+#[test]
+fn test_reach_goal() {
+    let mut project = load_project();
+    project.levels.truncate(1);
+
+    let mut harness = Harness::from_project(project, get_stats(), vec![LEFT; 600]);
+
+    // Right of the goal, over its floor
+    harness.teleport_player(Vec2::new(120.0, 1340.0));
+
+    let ticks = harness.run_until(600, |harness| *harness.state() != GameState::Game);
+
+    assert!(ticks < 600);
+    assert_eq!(*harness.state(), GameState::WinMenu);
+}
+
+#[test]
+fn test_campaign() {
+    let mut harness = Harness::new(get_stats(), vec![LEFT; 600]);
+
+    harness.teleport_player(Vec2::new(120.0, 1340.0));
+    harness.run_until(600, |harness| harness.level() == 1);
+    harness.run(1);
+
+    // Same player, at the rich spawn point of the second level
+    assert_eq!(*harness.state(), GameState::Game);
+    assert_eq!(harness.level(), 1);
+    assert_eq!(harness.level_problems(), Vec::<String>::new());
+    assert_eq!(harness.player_lifes(), Some(3));
+    assert!(
+        harness
+            .player_position()
+            .unwrap()
+            .distance(Vec2::new(184.0, 208.0))
+            < 8.0
+    );
+
+    // Right of the second goal, over its floor
+    harness.teleport_player(Vec2::new(372.0, 852.0));
+    harness.run_until(600, |harness| *harness.state() != GameState::Game);
+
+    assert_eq!(*harness.state(), GameState::WinMenu);
+}
+
+#[test]
+fn test_pause() {
+    let mut harness = Harness::new(get_stats(), vec![LEFT; 120]);
+    harness.run(30);
+
+    let position = harness.player_position();
+
+    harness
+        .app
+        .world
+        .resource_mut::<State<GameState>>()
+        .push(GameState::Paused)
+        .unwrap();
+    harness.run(30);
+
+    assert_eq!(*harness.state(), GameState::Paused);
+    assert_eq!(harness.player_position(), position);
+
+    harness
+        .app
+        .world
+        .resource_mut::<State<GameState>>()
+        .pop()
+        .unwrap();
+    harness.run(30);
+
+    assert_eq!(*harness.state(), GameState::Game);
+    assert_ne!(harness.player_position(), position);
+}
+
+#[test]
+fn test_invalid_level() {
+    let mut project = load_project();
+
+    for layer in project.levels[0].layer_instances.iter_mut().flatten() {
+        layer
+            .entity_instances
+            .retain(|entity| entity.identifier != "Goal");
+    }
+
+    let mut harness = Harness::from_project(project, get_stats(), vec![]);
+    harness.run(1);
+
+    assert_eq!(*harness.state(), GameState::MainMenu);
+    assert_eq!(harness.player_position(), None);
+    assert_eq!(
+        harness.level_problems(),
+        vec![String::from("there is no Goal")]
+    );
+}
+
+#[test]
+fn test_same_inputs_same_run() {
+    let mut inputs = vec![LEFT; 30];
+    inputs.extend(vec![
+        PlayerInput {
+            left: false,
+            right: true,
+            jump: true,
+            jump_held: true,
+            down: false,
+        };
+        60
+    ]);
+
+    let mut first = Harness::new(get_stats(), inputs.clone());
+    let mut second = Harness::new(get_stats(), inputs);
+
+    first.run(90);
+    second.run(90);
+
+    assert_ne!(first.player_position(), None);
+    assert_eq!(first.player_position(), second.player_position());
+}
+
+#[test]
+fn test_step_plays_like_inputs() {
+    let mut inputs = vec![LEFT; 40];
+    inputs.extend(vec![PlayerInput::default(); 20]);
+
+    let mut scripted = Harness::new(get_stats(), inputs.clone());
+    let mut stepped = Harness::new(get_stats(), vec![]);
+
+    scripted.run(inputs.len());
+
+    for input in inputs {
+        stepped.step(input);
+    }
+
+    assert_eq!(scripted.player_position(), stepped.player_position());
+}
+
+#[test]
+fn test_moving_platform_carries_player() {
+    let mut project = load_project();
+    add_entity(
+        &mut project,
+        "MovingPlatform",
+        PLATFORM_GRID,
+        vec![
+            ("path", FieldValue::Points(vec![Some(IVec2::new(17, 5))])),
+            ("speed", FieldValue::Float(Some(48.0))),
+        ],
+    );
+
+    let mut harness = Harness::from_project(project, get_stats(), vec![]);
+
+    harness.teleport_player(Vec2::new(ON_PLATFORM_X, ON_PLATFORM_Y + 2.0));
+    harness.run(60);
+
+    let position = harness.player_position().unwrap();
+
+    assert!(position.x > ON_PLATFORM_X + 40.0, "{position}");
+    assert!((position.y - ON_PLATFORM_Y).abs() < 1.0, "{position}");
+}
+
+#[test]
+fn test_moving_platform_stops_under_ceiling() {
+    let mut project = load_project();
+    // Rising right up to the level ceiling
+    add_entity(
+        &mut project,
+        "MovingPlatform",
+        PLATFORM_GRID,
+        vec![
+            ("path", FieldValue::Points(vec![Some(IVec2::new(12, 1))])),
+            ("speed", FieldValue::Float(Some(48.0))),
+        ],
+    );
+
+    let mut harness = Harness::from_project(project, get_stats(), vec![]);
+    // Head touching the ceiling
+    let highest = 1440.0 - 16.0 - (ON_PLATFORM_Y - 1360.0);
+    let mut apex = ON_PLATFORM_Y;
+
+    harness.teleport_player(Vec2::new(ON_PLATFORM_X, ON_PLATFORM_Y + 2.0));
+
+    for _ in 0..120 {
+        harness.run(1);
+        apex = apex.max(harness.player_position().unwrap().y);
+    }
+
+    assert!(apex <= highest, "{apex}");
+    // Still standing on it
+    assert_eq!(harness.player_position().unwrap().y, highest);
+}
+
+#[test]
+fn test_crumbling_platform() {
+    let mut project = load_project();
+    add_entity(
+        &mut project,
+        "CrumblingPlatform",
+        PLATFORM_GRID,
+        vec![
+            ("delay", FieldValue::Float(Some(0.5))),
+            ("respawn", FieldValue::Float(Some(1.0))),
+            ("one_way", FieldValue::Bool(true)),
+        ],
+    );
+
+    let mut harness = Harness::from_project(project, get_stats(), vec![]);
+
+    harness.teleport_player(Vec2::new(ON_PLATFORM_X, ON_PLATFORM_Y + 2.0));
+    harness.run(20);
+
+    let position = harness.player_position().unwrap();
+    assert!((position.y - ON_PLATFORM_Y).abs() < 1.0, "{position}");
+
+    // Crumbled under the player
+    harness.run(30);
+
+    let position = harness.player_position().unwrap();
+    assert!(position.y < ON_PLATFORM_Y - 16.0, "{position}");
+
+    // Back after respawning
+    harness.run(50);
+    harness.teleport_player(Vec2::new(ON_PLATFORM_X, ON_PLATFORM_Y + 2.0));
+    harness.run(20);
+
+    let position = harness.player_position().unwrap();
+    assert!((position.y - ON_PLATFORM_Y).abs() < 1.0, "{position}");
+}
+
+#[test]
+fn test_solid_platform() {
+    let add_solid_platform = |grid| {
+        let mut project = load_project();
+        add_entity(
+            &mut project,
+            "CrumblingPlatform",
+            grid,
+            vec![
+                ("delay", FieldValue::Float(Some(10.0))),
+                ("respawn", FieldValue::Float(Some(1.0))),
+                ("one_way", FieldValue::Bool(false)),
+            ],
+        );
+
+        project
+    };
+    // Standing on a long floor, left of the platforms
+    let start = Vec2::new(104.0, 786.0);
+    let right = PlayerInput {
+        right: true,
+        ..Default::default()
+    };
+
+    // At head height, in the way
+    let mut harness = Harness::from_project(
+        add_solid_platform(IVec2::new(8, 40)),
+        get_stats(),
+        vec![right; 60],
+    );
+    harness.teleport_player(start);
+    harness.run(60);
+
+    assert_eq!(harness.player_position().unwrap().x, 128.0 - 8.0);
+
+    // Right over the head, the jump hits it like a ceiling
+    let jump = PlayerInput {
+        jump: true,
+        jump_held: true,
+        ..Default::default()
+    };
+    let mut inputs = vec![right; 10];
+    inputs.extend(vec![jump; 30]);
+
+    let mut harness = Harness::from_project(
+        add_solid_platform(IVec2::new(8, 38)),
+        get_stats(),
+        inputs.clone(),
+    );
+    harness.teleport_player(start);
+
+    let mut apex = start.y;
+
+    for _ in 0..inputs.len() {
+        harness.run(1);
+        apex = apex.max(harness.player_position().unwrap().y);
+    }
+
+    assert!(apex <= 816.0 - 18.0, "{apex}");
+}
+
+#[test]
+fn test_checkpoint_respawn() {
+    // Standing on the lowest checkpoint of the first level
+    let checkpoint = Vec2::new(168.0, 514.0);
+    // Standing at the bottom, where the goo comes first
+    let bottom = Vec2::new(152.0, 66.0);
+    let run = |wealth| {
+        // Without a family to catch them before the goo
+        let mut stats = Stats {
+            wealth,
+            has_supportive_family: false,
+            ..get_stats()
+        };
+        stats.apply_tuning(&load_tuning());
+
+        let lifes = stats.lifes;
+        let mut harness = Harness::new(stats, vec![]);
+
+        harness.teleport_player(checkpoint);
+        harness.run(10);
+        harness.teleport_player(bottom);
+        harness.run_until(3000, |harness| harness.run_stats().goo_hits > 0);
+
+        assert_eq!(harness.player_lifes(), Some(lifes - 1));
+
+        harness.player_position().unwrap()
+    };
+
+    assert_eq!(run(Wealth::Rich), checkpoint);
+    // The poor can't use checkpoints, they stay where the goo caught them
+    assert_eq!(run(Wealth::Poor), bottom);
+}
+
+#[test]
+fn test_goo_hit_after_bounce() {
+    let mut project = load_project();
+    set_goo_settings(
+        &mut project,
+        vec![
+            // Right under the lowest spawn point
+            ("start", FieldValue::Float(Some(40.0))),
+            ("speed", FieldValue::Float(Some(0.0))),
+            ("wave_amplitude", FieldValue::Float(Some(0.0))),
+        ],
+    );
+
+    // Without a family to catch them before the goo
+    let mut stats = Stats {
+        wealth: Wealth::Poor,
+        has_supportive_family: false,
+        ..get_stats()
+    };
+    stats.apply_tuning(&load_tuning());
+
+    let lifes = stats.lifes;
+    let mut harness = Harness::from_project(project, stats, vec![]);
+
+    // In the `wealth==rich` bouncer, away from the goo
+    harness.teleport_player(Vec2::new(352.0, 880.0));
+    harness.run(1);
+
+    assert_eq!(harness.run_stats().bounces, 1);
+    assert_eq!(harness.player_lifes(), Some(lifes));
+
+    // Bouncing blinks the player but doesn't protect them from the goo under the level floor
+    harness.teleport_player(Vec2::new(152.0, 14.0));
+    harness.run(1);
+
+    assert_eq!(harness.run_stats().goo_hits, 1);
+    assert_eq!(harness.player_lifes(), Some(lifes - 1));
+}
+
+#[test]
+fn test_goo_settings() {
+    let mut project = load_project();
+    set_goo_settings(
+        &mut project,
+        vec![
+            // Already over the player
+            ("start", FieldValue::Float(Some(400.0))),
+            ("speed", FieldValue::Float(Some(0.0))),
+        ],
+    );
+
+    let mut harness = Harness::from_project(project, get_stats(), vec![]);
+    harness.run(10);
+
+    assert_eq!(harness.run_stats().family_rescues, 1);
+
+    // Without settings the tuning goo starts under the level
+    let mut project = load_project();
+
+    for layer in project.levels[0].layer_instances.iter_mut().flatten() {
+        layer
+            .entity_instances
+            .retain(|entity| entity.identifier != "GooSettings");
+    }
+
+    let mut harness = Harness::from_project(project, get_stats(), vec![]);
+    harness.run(10);
+
+    assert_eq!(harness.run_stats().family_rescues, 0);
+    assert_eq!(harness.goo().1, Color::BLACK);
+}
+
+#[test]
+fn test_goo_curve_and_color() {
+    let mut project = load_project();
+    set_goo_settings(
+        &mut project,
+        vec![
+            ("start", FieldValue::Float(Some(-500.0))),
+            ("speed", FieldValue::Float(Some(60.0))),
+            ("curve", FieldValue::String(Some(String::from("pausing")))),
+            ("pause_every", FieldValue::Float(Some(1.0))),
+            ("pause_for", FieldValue::Float(Some(1.0))),
+            ("wave_amplitude", FieldValue::Float(Some(0.0))),
+            ("catch_up_distance", FieldValue::Float(Some(0.0))),
+            ("color", FieldValue::Color(Color::RED)),
+        ],
+    );
+
+    let mut harness = Harness::from_project(project, get_stats(), vec![]);
+    let (start, color) = harness.goo();
+
+    assert!((start - -500.0).abs() <= 1.0, "{start}");
+    assert_eq!(color, Color::RED);
+
+    // A px per step while rising
+    harness.run(30);
+    let (rising, _) = harness.goo();
+
+    assert!((rising - start - 30.0).abs() < 0.01, "{rising}");
+
+    // Paused from the first to the second second
+    harness.run(40);
+    let (pause_start, _) = harness.goo();
+    harness.run(40);
+    let (pause_end, _) = harness.goo();
+
+    assert_eq!(pause_start, pause_end);
+
+    harness.run(40);
+    let (rising, _) = harness.goo();
+
+    assert!(rising > pause_end + 20.0, "{rising}");
+}
+
+#[test]
+fn test_goo_wave() {
+    let mut project = load_project();
+    set_goo_settings(
+        &mut project,
+        vec![
+            ("start", FieldValue::Float(Some(-500.0))),
+            ("speed", FieldValue::Float(Some(0.0))),
+            ("wave_amplitude", FieldValue::Float(Some(8.0))),
+            ("wave_period", FieldValue::Float(Some(1.0))),
+            ("catch_up_distance", FieldValue::Float(Some(0.0))),
+        ],
+    );
+
+    let mut harness = Harness::from_project(project, get_stats(), vec![]);
+    let mut heights = vec![];
+
+    // A whole period
+    for _ in 0..60 {
+        harness.run(1);
+        heights.push(harness.goo().0);
+    }
+
+    let highest = heights.iter().cloned().reduce(f32::max).unwrap();
+    let lowest = heights.iter().cloned().reduce(f32::min).unwrap();
+
+    assert!((highest - -492.0).abs() < 0.1, "{highest}");
+    assert!((lowest - -508.0).abs() < 0.1, "{lowest}");
+}